@@ -0,0 +1,135 @@
+use crate::{
+    Direction,
+    save::{self, Reader, Save, Token, Writer},
+};
+use color_eyre::Result;
+use multid::BoundedIx2;
+use std::collections::BTreeMap;
+
+/// most marks that fit on one door, as in Trémaux's algorithm
+pub const MAX_DOOR_MARKS: usize = 2;
+
+/// chalk marks the player has left in one room
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Marks {
+    /// a cross on the floor
+    pub room: bool,
+    /// a tick by a door for each time it was marked
+    pub doors: Vec<Direction>,
+}
+
+impl Marks {
+    pub fn on_door(&self, dir: Direction) -> usize {
+        self.doors.iter().filter(|d| **d == dir).count()
+    }
+}
+
+/// every mark the player has made, and how much chalk they've got left
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Chalk<const N_ROWS: usize, const N_COLS: usize> {
+    marks: BTreeMap<BoundedIx2<N_ROWS, N_COLS>, Marks>,
+    /// marks left to make, if there's a limit
+    left: Option<usize>,
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Chalk<N_ROWS, N_COLS> {
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            marks: BTreeMap::new(),
+            left: limit,
+        }
+    }
+    pub fn get(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> Option<&Marks> {
+        self.marks.get(&ix)
+    }
+    /// put a cross on the floor of `ix`, unless there already is one or the chalk's run out
+    pub fn mark_room(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) -> bool {
+        if self.get(ix).is_some_and(|m| m.room) || !self.use_up() {
+            return false;
+        }
+        self.marks.entry(ix).or_default().room = true;
+        true
+    }
+    /// add a tick by the door of `ix` in direction `dir`, unless it's got all the ticks it can
+    /// take or the chalk's run out
+    pub fn mark_door(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>, dir: Direction) -> bool {
+        if self
+            .get(ix)
+            .is_some_and(|m| m.on_door(dir) >= MAX_DOOR_MARKS)
+            || !self.use_up()
+        {
+            return false;
+        }
+        self.marks.entry(ix).or_default().doors.push(dir);
+        true
+    }
+    fn use_up(&mut self) -> bool {
+        match &mut self.left {
+            None => true,
+            Some(0) => false,
+            Some(left) => {
+                *left -= 1;
+                true
+            }
+        }
+    }
+    /// what to show in the HUD, if the chalk can run out
+    pub fn status(&self) -> Option<String> {
+        self.left.map(|left| format!("chalk {left}"))
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Save for Chalk<N_ROWS, N_COLS> {
+    /// each marked room as `row,col,cross,ticks`, e.g. `2,3,x,nne` for a cross on the floor,
+    /// two ticks by the north door and one by the east door
+    fn save(&self, out: &mut Writer) {
+        out.field("chalk_left", save::optional(self.left));
+        out.list(
+            "marks",
+            self.marks.iter().map(|(ix, marks)| {
+                let ticks: String = marks.doors.iter().map(|dir| dir.token()).collect();
+                let cross = if marks.room { "x" } else { "-" };
+                format!("{},{cross},{ticks}", save::ix(*ix))
+            }),
+        );
+    }
+    fn load(input: &mut Reader) -> Result<Self> {
+        let left = input.optional("chalk_left")?;
+        let marks = input.list("marks", |s| {
+            let (ix, rest) = save::parse_ix(s)?;
+            let (cross, ticks) = rest.split_once(',')?;
+            let room = match cross {
+                "x" => true,
+                "-" => false,
+                _ => return None,
+            };
+            let doors = ticks
+                .chars()
+                .map(|c| Direction::from_token(&c.to_string()))
+                .collect::<Option<_>>()?;
+            Some((ix, Marks { room, doors }))
+        })?;
+        Ok(Self {
+            marks: marks.into_iter().collect(),
+            left,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_runs_out() {
+        let ix = BoundedIx2::<3, 3>::new(1, 1).unwrap();
+        let mut chalk = Chalk::new(Some(3));
+        assert!(chalk.mark_room(ix));
+        assert!(!chalk.mark_room(ix), "already marked");
+        assert!(chalk.mark_door(ix, Direction::East));
+        assert!(chalk.mark_door(ix, Direction::East));
+        assert!(!chalk.mark_door(ix, Direction::West), "out of chalk");
+        assert_eq!(Some("chalk 0".into()), chalk.status());
+        assert_eq!(2, chalk.get(ix).unwrap().on_door(Direction::East));
+    }
+}
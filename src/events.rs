@@ -0,0 +1,130 @@
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use std::{collections::VecDeque, fs, path::Path, sync::mpsc::Receiver};
+
+/// where the games get their key presses from
+pub trait EventSource {
+    /// the next event, or `None` once there aren't going to be any more
+    fn next_event(&mut self) -> Result<Option<Event>>;
+}
+
+/// key presses from the terminal, waiting for each one
+#[derive(Debug, Default)]
+pub struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        Ok(Some(event::read()?))
+    }
+}
+
+/// a fixed list of events, played back in order
+#[derive(Debug, Default, Clone)]
+pub struct Scripted {
+    events: VecDeque<Event>,
+}
+
+impl Scripted {
+    pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+    /// a key press for each character of `keys`, e.g. `"ddsq"`
+    pub fn keys(keys: &str) -> Self {
+        Self::new(keys.chars().map(|c| key(KeyCode::Char(c))))
+    }
+}
+
+impl EventSource for Scripted {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        Ok(self.events.pop_front())
+    }
+}
+
+/// key presses saved in a file, one per line: either a single character, or one of `up`,
+/// `down`, `left`, `right`, `enter`, `esc`, `backspace` or `space`. blank lines and lines
+/// starting with `#` are skipped
+#[derive(Debug, Clone)]
+pub struct Replay(Scripted);
+
+impl Replay {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .wrap_err_with(|| format!("couldn't read {}", path.display()))?;
+        text.parse()
+    }
+}
+
+impl std::str::FromStr for Replay {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut events = Vec::new();
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let code = match line.to_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                _ => {
+                    let mut chars = line.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => KeyCode::Char(c),
+                        _ => bail!("line {}: unknown key {line:?}", n + 1),
+                    }
+                }
+            };
+            events.push(key(code));
+        }
+        Ok(Self(Scripted::new(events)))
+    }
+}
+
+impl EventSource for Replay {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        self.0.next_event()
+    }
+}
+
+/// events sent from another thread; stops when the sender hangs up
+#[derive(Debug)]
+pub struct Channel(pub Receiver<Event>);
+
+impl EventSource for Channel {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        Ok(self.0.recv().ok())
+    }
+}
+
+/// a plain press of `code`
+pub fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_replay() {
+        let mut replay: Replay = "# a comment\nd\n\nUp\nspace\n".parse().unwrap();
+        assert_eq!(Some(key(KeyCode::Char('d'))), replay.next_event().unwrap());
+        assert_eq!(Some(key(KeyCode::Up)), replay.next_event().unwrap());
+        assert_eq!(Some(key(KeyCode::Char(' '))), replay.next_event().unwrap());
+        assert_eq!(None, replay.next_event().unwrap());
+        assert!("dd\n".parse::<Replay>().is_err());
+    }
+}
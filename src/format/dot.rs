@@ -0,0 +1,113 @@
+//! the maze as a graphviz graph, for seeing what a generator actually made
+//!
+//! every room is a node pinned to where it is in the grid, and every open door is an edge.
+//! render it with `neato -n` (or `fdp`) so the positions stick, e.g.
+//! `neato -n -Tsvg maze.dot > maze.svg`
+
+use crate::{
+    Direction,
+    maze::{DoorState, Maze, RoomKind},
+};
+use multid::BoundedIx2;
+use std::fmt::Write;
+
+/// points between neighbouring rooms
+const SPACING: usize = 72;
+
+fn node<const N_ROWS: usize, const N_COLS: usize>(ix: BoundedIx2<N_ROWS, N_COLS>) -> String {
+    format!("r{}c{}", ix.y(), ix.x())
+}
+
+/// the open-door graph of the maze in dot. the start is a green double circle, goals are red
+/// ones (numbered if there's more than one), dead ends are grey, crossings are small boxes and
+/// doors that wrap round the edges are dashed
+pub fn to_dot<const N_ROWS: usize, const N_COLS: usize>(maze: &Maze<N_ROWS, N_COLS>) -> String {
+    let mut dot = String::from("graph maze {\n");
+    writeln!(
+        dot,
+        "  node [shape=circle, fixedsize=true, width=0.5, fontsize=10];"
+    )
+    .unwrap();
+    for ix in maze.indices() {
+        let room = &maze.rooms[ix];
+        let n_open = room
+            .all_doors()
+            .filter(|(_, st)| *st == DoorState::Open)
+            .count();
+        let mut attrs = vec![
+            format!(
+                "pos=\"{},{}\"",
+                ix.x() * SPACING,
+                (N_ROWS - 1 - ix.y()) * SPACING
+            ),
+            format!("label=\"{},{}\"", ix.y(), ix.x()),
+        ];
+        match (room.kind, maze.goals.position(ix)) {
+            (RoomKind::Crossing { .. }, _) => {
+                attrs.push("shape=box, width=0.3, height=0.3, label=\"\"".into())
+            }
+            (_, Some(n)) => {
+                attrs.push("shape=doublecircle, color=red".into());
+                if maze.goals.len() > 1 {
+                    attrs[1] = format!("label=\"{}\"", n + 1);
+                }
+            }
+            _ if ix == maze.current_ix => (),
+            _ if n_open <= 1 => attrs.push("style=filled, fillcolor=lightgrey".into()),
+            _ => (),
+        }
+        if ix == maze.current_ix {
+            attrs.push("shape=doublecircle, color=green, penwidth=2".into());
+        }
+        writeln!(dot, "  {} [{}];", node(ix), attrs.join(", ")).unwrap();
+    }
+    // east and south doors only, so each one is drawn once
+    for ix in maze.indices() {
+        for dir in [Direction::East, Direction::South] {
+            if maze.rooms[ix].doors.get(dir) != Some(DoorState::Open) {
+                continue;
+            }
+            let Some(next) = maze.neighbor(ix, dir) else {
+                continue;
+            };
+            let style = if maze.wrap_doors(ix).contains(&dir) {
+                " [style=dashed, color=cyan4]"
+            } else {
+                ""
+            };
+            writeln!(dot, "  {} -- {}{style};", node(ix), node(next)).unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::test_maze;
+
+    #[test]
+    fn test_dot() {
+        let maze = test_maze(3);
+        let dot = to_dot(&maze);
+        assert!(dot.starts_with("graph maze {\n"));
+        assert!(dot.ends_with("}\n"));
+        let n_open: usize = maze
+            .indices()
+            .map(|ix| {
+                maze.rooms[ix]
+                    .all_doors()
+                    .filter(|(_, st)| *st == DoorState::Open)
+                    .count()
+            })
+            .sum();
+        assert_eq!(n_open / 2, dot.matches(" -- ").count());
+        assert_eq!(maze.indices().count(), dot.matches("pos=").count());
+        let start = dot
+            .lines()
+            .find(|l| l.starts_with(&format!("  {} [", node(maze.current_ix))))
+            .unwrap();
+        assert!(start.contains("color=green"));
+    }
+}
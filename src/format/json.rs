@@ -0,0 +1,285 @@
+//! mazes as json, for tools that want to read them without linking this crate
+//!
+//! rooms, doors and the rest serialize as themselves, but a whole [`Maze`] goes through
+//! [`MazeSchema`] so the layout stays the same however the maze is stored inside. the
+//! schema has a version, and anything changing what's already there has to bump it
+
+use crate::{
+    goals::{Goals, WinCondition},
+    mask::Mask,
+    maze::{Item, Maze, Room},
+};
+use color_eyre::{Result, eyre::bail};
+use multid::BoundedIx2;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+/// version of [`MazeSchema`] this build writes, and the only one it reads
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// a maze as it's written to json
+///
+/// rooms are `[row][col]`, with `null` for empty space, and positions are `[row, col]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MazeSchema {
+    pub version: u32,
+    pub rows: usize,
+    pub cols: usize,
+    /// whether doors on the edges lead around to the opposite edge
+    pub wrap: bool,
+    pub rooms: Vec<Vec<Option<Room>>>,
+    /// where the player is
+    pub current: [usize; 2],
+    /// goals in order, the last one being where the maze was carved towards
+    pub goals: Vec<GoalSchema>,
+    pub win: WinCondition,
+    pub score: usize,
+    pub collected: usize,
+    pub items_required: usize,
+    /// what the player picked up last, if anything
+    pub last_pickup: Option<Item>,
+    /// every room the player has stopped in, in order
+    pub trail: Vec<[usize; 2]>,
+    pub undos_left: Option<usize>,
+    pub undo_cost: usize,
+}
+
+/// a goal and whether the player has got to it yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalSchema {
+    pub pos: [usize; 2],
+    pub reached: bool,
+}
+
+fn pos<const N_ROWS: usize, const N_COLS: usize>(ix: BoundedIx2<N_ROWS, N_COLS>) -> [usize; 2] {
+    [ix.y(), ix.x()]
+}
+
+fn ix<const N_ROWS: usize, const N_COLS: usize>(
+    [row, col]: [usize; 2],
+) -> Result<BoundedIx2<N_ROWS, N_COLS>> {
+    match BoundedIx2::new(row, col) {
+        Some(ix) => Ok(ix),
+        None => bail!("[{row}, {col}] is outside a {N_ROWS}x{N_COLS} maze"),
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> From<&Maze<N_ROWS, N_COLS>> for MazeSchema {
+    fn from(maze: &Maze<N_ROWS, N_COLS>) -> Self {
+        let rooms = (0..N_ROWS)
+            .map(|row| {
+                (0..N_COLS)
+                    .map(|col| {
+                        let ix = BoundedIx2::new(row, col).unwrap();
+                        maze.is_room(ix).then(|| maze.rooms[ix].clone())
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            version: SCHEMA_VERSION,
+            rows: N_ROWS,
+            cols: N_COLS,
+            wrap: maze.wrap,
+            rooms,
+            current: pos(maze.current_ix),
+            goals: maze
+                .goals
+                .iter()
+                .map(|goal| GoalSchema {
+                    pos: pos(goal),
+                    reached: maze.goals.is_reached(goal),
+                })
+                .collect(),
+            win: maze.goals.condition,
+            score: maze.score,
+            collected: maze.collected,
+            items_required: maze.items_required,
+            last_pickup: maze.last_pickup,
+            trail: maze.trail.iter().copied().map(pos).collect(),
+            undos_left: maze.undos_left,
+            undo_cost: maze.undo_cost,
+        }
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> TryFrom<MazeSchema> for Maze<N_ROWS, N_COLS> {
+    type Error = color_eyre::Report;
+
+    /// the maze a schema describes; moves made before it was written can't be undone
+    fn try_from(schema: MazeSchema) -> Result<Self> {
+        if schema.version != SCHEMA_VERSION {
+            bail!(
+                "schema version {} isn't supported, only {SCHEMA_VERSION}",
+                schema.version
+            );
+        }
+        if (schema.rows, schema.cols) != (N_ROWS, N_COLS) {
+            bail!(
+                "maze is {}x{}, this one's {N_ROWS}x{N_COLS}",
+                schema.rows,
+                schema.cols
+            );
+        }
+        if schema.rooms.len() != N_ROWS || schema.rooms.iter().any(|row| row.len() != N_COLS) {
+            bail!("rooms should be {N_ROWS} rows of {N_COLS}");
+        }
+        let mask: Mask<N_ROWS, N_COLS> = schema
+            .rooms
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|room| if room.is_some() { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .parse()?;
+        let mut maze = Maze::with_mask(mask, schema.wrap);
+        for (row, rooms) in schema.rooms.into_iter().enumerate() {
+            for (col, room) in rooms.into_iter().enumerate() {
+                if let Some(room) = room {
+                    maze.rooms[ix([row, col])?] = room;
+                }
+            }
+        }
+        maze.current_ix = ix(schema.current)?;
+        let goals = schema
+            .goals
+            .iter()
+            .map(|goal| Ok((ix(goal.pos)?, goal.reached)))
+            .collect::<Result<Vec<_>>>()?;
+        maze.goals = Goals::with_reached(goals, schema.win)?;
+        maze.score = schema.score;
+        maze.collected = schema.collected;
+        maze.items_required = schema.items_required;
+        maze.last_pickup = schema.last_pickup;
+        maze.trail = schema.trail.into_iter().map(ix).collect::<Result<_>>()?;
+        maze.undos_left = schema.undos_left;
+        maze.undo_cost = schema.undo_cost;
+        maze.check()?;
+        Ok(maze)
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Serialize for Maze<N_ROWS, N_COLS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MazeSchema::from(self).serialize(serializer)
+    }
+}
+
+impl<'de, const N_ROWS: usize, const N_COLS: usize> Deserialize<'de> for Maze<N_ROWS, N_COLS> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let schema = MazeSchema::deserialize(deserializer)?;
+        Maze::try_from(schema).map_err(|e| de::Error::custom(format!("{e:#}")))
+    }
+}
+
+/// the maze as pretty-printed json
+pub fn to_json<const N_ROWS: usize, const N_COLS: usize>(
+    maze: &Maze<N_ROWS, N_COLS>,
+) -> Result<String> {
+    Ok(serde_json::to_string_pretty(maze)?)
+}
+
+pub fn from_json<const N_ROWS: usize, const N_COLS: usize>(
+    json: &str,
+) -> Result<Maze<N_ROWS, N_COLS>> {
+    Ok(serde_json::from_str(json)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        game::test_maze,
+        maze::{Axis, DoorState, RoomKind},
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let mut maze = test_maze(3);
+        // part way through, with the first checkpoint already reached
+        let checkpoint = maze.goals.iter().next().unwrap();
+        maze.goals.condition = WinCondition::Ordered;
+        maze.goals.visit(checkpoint);
+        maze.last_pickup = Some(Item::Gem);
+        let json = to_json(&maze).unwrap();
+        let loaded: Maze<7, 7> = from_json(&json).unwrap();
+        assert_eq!(format!("{:?}", maze.rooms), format!("{:?}", loaded.rooms));
+        assert_eq!(maze.goals, loaded.goals);
+        assert!(loaded.goals.is_reached(checkpoint));
+        assert_eq!(1, loaded.goals.n_reached());
+        assert_eq!(maze.current_ix, loaded.current_ix);
+        assert_eq!(Some(Item::Gem), loaded.last_pickup);
+        assert_eq!(json, to_json(&loaded).unwrap());
+        assert!(from_json::<7, 6>(&json).is_err());
+        let newer = json.replace("\"version\": 1", "\"version\": 2");
+        assert!(from_json::<7, 7>(&newer).is_err());
+    }
+
+    #[test]
+    fn test_schema() {
+        let json = to_json(&Maze::<1, 2>::new()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(1, value["version"]);
+        assert_eq!("closed", value["rooms"][0][0]["doors"]["east"]);
+        assert_eq!(
+            serde_json::Value::Null,
+            value["rooms"][0][0]["doors"]["west"]
+        );
+        assert_eq!("plain", value["rooms"][0][1]["kind"]);
+        assert_eq!(
+            serde_json::json!([{ "pos": [0, 1], "reached": false }]),
+            value["goals"]
+        );
+    }
+
+    #[test]
+    fn test_bad_mazes() {
+        let schema = MazeSchema::from(&Maze::<1, 3>::new());
+        let load = |change: fn(&mut MazeSchema)| {
+            let mut schema = schema.clone();
+            change(&mut schema);
+            Maze::<1, 3>::try_from(schema)
+        };
+        assert!(load(|_| ()).is_ok());
+        assert!(
+            load(|s| {
+                s.rooms[0][1].as_mut().unwrap().kind = RoomKind::Crossing {
+                    over: Axis::EastWest,
+                }
+            })
+            .is_err(),
+            "crossing without a door north or south"
+        );
+        assert!(
+            load(|s| s.rooms[0][0].as_mut().unwrap().doors.east = Some(DoorState::Open)).is_err(),
+            "door that doesn't match the one back"
+        );
+        assert!(
+            load(|s| s.rooms[0][0].as_mut().unwrap().doors.west = Some(DoorState::Closed)).is_err(),
+            "door in the outside wall"
+        );
+        assert!(
+            load(|s| {
+                s.rooms[0][2] = None;
+                s.rooms[0][1].as_mut().unwrap().doors.east = None;
+                s.goals = vec![GoalSchema {
+                    pos: [0, 1],
+                    reached: false,
+                }];
+                s.current = [0, 2];
+            })
+            .is_err(),
+            "current room masked out"
+        );
+        assert!(
+            load(|s| {
+                s.rooms[0][2] = None;
+                s.rooms[0][1].as_mut().unwrap().doors.east = None;
+            })
+            .is_err(),
+            "goal masked out"
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! reading and writing mazes in formats other programs (and people) can use
+
+use crate::ui;
+use ratatui::style::Color;
+
+pub mod dot;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod ppm;
+pub mod svg;
+pub mod text;
+
+/// `color` as red, green and blue, the same as [`ui::rgb`] except that with `print` the
+/// background is white and the walls black, to save ink
+fn rgb(color: Color, print: bool) -> (u8, u8, u8) {
+    match color {
+        _ if print && color == ui::BG_COLOR => (255, 255, 255),
+        _ if print && color == ui::WALL_COLOR => (0, 0, 0),
+        _ => ui::rgb(color),
+    }
+}
@@ -0,0 +1,168 @@
+//! mazes as pictures, for thumbnails and posters
+//!
+//! the maze is drawn from the same lines as [`RoomView`] at however many pixels a room should
+//! take up, and written as a binary ppm, which almost anything can open or convert
+
+use crate::{
+    format,
+    maze::Maze,
+    ui::{self, RoomView},
+};
+use color_eyre::{Result, eyre::WrapErr};
+use multid::BoundedIx2;
+use ratatui::style::Color;
+use std::{fs, path::Path};
+
+/// pixels across a room unless asked for something else
+pub const DEFAULT_ROOM_PX: usize = 24;
+const START_COLOR: Color = Color::Magenta;
+const GOAL_COLOR: Color = Color::Red;
+const REACHED_GOAL_COLOR: Color = Color::Green;
+
+#[derive(Clone, Debug)]
+pub struct RasterOptions {
+    /// pixels across each room
+    pub room_px: usize,
+    /// black walls on white instead of the game's colours on black
+    pub print: bool,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            room_px: DEFAULT_ROOM_PX,
+            print: false,
+        }
+    }
+}
+
+/// a picture as rows of rgb pixels, top to bottom
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<(u8, u8, u8)>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: (u8, u8, u8)) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+    pub fn get(&self, x: usize, y: usize) -> (u8, u8, u8) {
+        self.pixels[y * self.width + x]
+    }
+    /// fill in a rectangle, clipped to the edges of the picture
+    pub fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, rgb: (u8, u8, u8)) {
+        for row in y..(y + height).min(self.height) {
+            for col in x..(x + width).min(self.width) {
+                self.pixels[row * self.width + col] = rgb;
+            }
+        }
+    }
+    /// as a binary (P6) ppm
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for (r, g, b) in &self.pixels {
+            ppm.extend([*r, *g, *b]);
+        }
+        ppm
+    }
+    pub fn write_ppm(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_ppm())
+            .wrap_err_with(|| format!("couldn't write {}", path.display()))
+    }
+}
+
+/// draw the maze with a square in the start room and in every goal
+pub fn rasterise<const N_ROWS: usize, const N_COLS: usize>(
+    maze: &Maze<N_ROWS, N_COLS>,
+    options: &RasterOptions,
+) -> Image {
+    let room_px = options.room_px.max(4);
+    let thickness = (room_px / 16).max(1);
+    let margin = thickness;
+    let scale = room_px as f64 / ui::ROOM_SIZE;
+    let rgb = |color: Color| format::rgb(color, options.print);
+    let mut image = Image::new(
+        N_COLS * room_px + 2 * margin,
+        N_ROWS * room_px + 2 * margin,
+        rgb(ui::BG_COLOR),
+    );
+    // canvas coordinates to pixels; the canvas's y goes up and the picture's goes down
+    let px = |v: f64| (v * scale).round() as usize + margin;
+    for ix in maze.indices() {
+        let view = RoomView {
+            x: ui::ROOM_SIZE * ix.x() as f64,
+            y: -ui::ROOM_SIZE * ix.y() as f64,
+            room: &maze.rooms[ix],
+            wrap_doors: maze.wrap_doors(ix),
+            fade: 0.0,
+            marks: None,
+        };
+        // every line of a room runs straight across or straight down
+        for line in view.lines() {
+            if line.color == ui::BG_COLOR {
+                continue;
+            }
+            let (x1, x2) = (px(line.x1.min(line.x2)), px(line.x1.max(line.x2)));
+            let (y1, y2) = (px(-line.y1.max(line.y2)), px(-line.y1.min(line.y2)));
+            let half = thickness / 2;
+            image.fill(
+                x1 - half,
+                y1 - half,
+                x2 - x1 + thickness,
+                y2 - y1 + thickness,
+                rgb(line.color),
+            );
+        }
+    }
+    let mut marker = |ix: BoundedIx2<N_ROWS, N_COLS>, color: Color| {
+        let size = room_px / 3;
+        let x = margin + ix.x() * room_px + (room_px - size) / 2;
+        let y = margin + ix.y() * room_px + (room_px - size) / 2;
+        image.fill(x, y, size, size, rgb(color));
+    };
+    for goal in maze.goals.iter() {
+        let color = if maze.goals.is_reached(goal) {
+            REACHED_GOAL_COLOR
+        } else {
+            GOAL_COLOR
+        };
+        marker(goal, color);
+    }
+    marker(maze.current_ix, START_COLOR);
+    image
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::test_maze;
+
+    #[test]
+    fn test_rasterise() {
+        let maze = test_maze(3);
+        let image = rasterise(
+            &maze,
+            &RasterOptions {
+                room_px: 32,
+                print: true,
+            },
+        );
+        // 32 pixels a room, lines 2 pixels thick and a margin of 2 all round
+        assert_eq!((228, 228), (image.width, image.height));
+        assert_eq!((255, 255, 255), image.get(0, 0));
+        assert_eq!((0, 0, 0), image.get(2, 2));
+        let centre = |ix: BoundedIx2<7, 7>| image.get(2 + ix.x() * 32 + 16, 2 + ix.y() * 32 + 16);
+        assert_eq!(format::rgb(START_COLOR, true), centre(maze.current_ix));
+        assert_eq!(format::rgb(GOAL_COLOR, true), centre(maze.goals.last()));
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n228 228\n255\n"));
+        assert_eq!(b"P6\n228 228\n255\n".len() + 228 * 228 * 3, ppm.len());
+    }
+}
@@ -0,0 +1,236 @@
+//! mazes as svg, for printing them out or putting them on a page
+//!
+//! the walls and doors are the same lines [`RoomView`] draws in the terminal, so a maze looks
+//! the same on paper as it does in the game
+
+use crate::{
+    Direction, format,
+    maze::Maze,
+    ui::{self, RoomView},
+};
+use multid::BoundedIx2;
+use ratatui::style::Color;
+use std::fmt::Write;
+
+/// space around the maze
+const MARGIN: f64 = ui::SEG_LEN;
+const LINE_WIDTH: f64 = 2.0;
+const SOLUTION_COLOR: Color = Color::Blue;
+
+/// what to draw on top of the maze
+#[derive(Clone, Debug, Default)]
+pub struct SvgOptions {
+    /// a way to win from where the player is, picking up any items that have to be on the way
+    pub solution: bool,
+    /// a breadcrumb in every room the player has been in
+    pub trail: bool,
+    /// black walls on white instead of the game's colours on black, to save ink
+    pub print: bool,
+}
+
+/// the maze as a standalone svg document
+pub fn to_svg<const N_ROWS: usize, const N_COLS: usize>(
+    maze: &Maze<N_ROWS, N_COLS>,
+    options: &SvgOptions,
+) -> String {
+    let width = ui::ROOM_SIZE * N_COLS as f64 + 2.0 * MARGIN;
+    let height = ui::ROOM_SIZE * N_ROWS as f64 + 2.0 * MARGIN;
+    let color = |color: Color| hex(color, options.print);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">\n"
+    );
+    writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        color(ui::BG_COLOR)
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<g stroke-width=\"{LINE_WIDTH}\" stroke-linecap=\"square\">"
+    )
+    .unwrap();
+    for ix in maze.indices() {
+        let view = RoomView {
+            x: ui::ROOM_SIZE * ix.x() as f64,
+            y: -ui::ROOM_SIZE * ix.y() as f64,
+            room: &maze.rooms[ix],
+            wrap_doors: maze.wrap_doors(ix),
+            fade: 0.0,
+            marks: None,
+        };
+        // the canvas's y goes up and svg's goes down
+        for line in view.lines() {
+            if line.color == ui::BG_COLOR {
+                continue;
+            }
+            writeln!(
+                svg,
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"/>",
+                line.x1 + MARGIN,
+                MARGIN - line.y1,
+                line.x2 + MARGIN,
+                MARGIN - line.y2,
+                color(line.color)
+            )
+            .unwrap();
+        }
+    }
+    svg.push_str("</g>\n");
+    if options.trail {
+        for ix in maze.indices().filter(|ix| maze.visits(*ix) > 0) {
+            // where `ui::Breadcrumb` puts it
+            let (x, y) = corner(ix);
+            writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                x + ui::SEG_LEN,
+                y + ui::SEG_LEN * 5.0,
+                ui::SEG_LEN,
+                ui::SEG_LEN,
+                color(ui::BREADCRUMB_COLOR)
+            )
+            .unwrap();
+        }
+    }
+    if options.solution
+        && let Some(path) = maze.solution()
+    {
+        solution(&mut svg, maze, &path, &color(SOLUTION_COLOR));
+    }
+    let font_size = ui::SEG_LEN * 3.0;
+    writeln!(
+        svg,
+        "<g font-size=\"{font_size}\" text-anchor=\"middle\" dominant-baseline=\"central\">"
+    )
+    .unwrap();
+    for ix in maze.indices() {
+        // the same as the basic game shows
+        let label = if ix == maze.current_ix && maze.goals.contains(ix) {
+            "\u{1f940}".to_string()
+        } else if ix == maze.current_ix {
+            "\u{1f600}".to_string()
+        } else if let Some(label) = ui::goal_label(&maze.goals, ix) {
+            label
+        } else if let Some(item) = maze.rooms[ix].item {
+            ui::item_label(item).to_string()
+        } else {
+            continue;
+        };
+        let (x, y) = centre(ix);
+        writeln!(
+            svg,
+            "<text x=\"{x}\" y=\"{y}\" fill=\"{}\">{label}</text>",
+            color(ui::WALL_COLOR)
+        )
+        .unwrap();
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// a line through the middle of every room on `path`, split in two where it goes off one edge
+/// of a wrapping maze and comes back on the other
+fn solution<const N_ROWS: usize, const N_COLS: usize>(
+    svg: &mut String,
+    maze: &Maze<N_ROWS, N_COLS>,
+    path: &[BoundedIx2<N_ROWS, N_COLS>],
+    color: &str,
+) {
+    let mut line = |(x1, y1): (f64, f64), (x2, y2): (f64, f64)| {
+        writeln!(
+            svg,
+            "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"{color}\" \
+             stroke-width=\"{LINE_WIDTH}\" stroke-linecap=\"round\"/>"
+        )
+        .unwrap();
+    };
+    for pair in path.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let Some((dir, _)) = maze.moves_from(from).find(|(_, next)| *next == to) else {
+            continue;
+        };
+        let (d_row, d_col) = dir.offset();
+        let forwards = (to.y() as isize - from.y() as isize).signum() == d_row
+            && (to.x() as isize - from.x() as isize).signum() == d_col;
+        if forwards {
+            line(centre(from), centre(to));
+        } else {
+            line(centre(from), edge(from, dir));
+            line(edge(to, dir.opposite()), centre(to));
+        }
+    }
+}
+
+/// top left corner of a room in the svg
+fn corner<const N_ROWS: usize, const N_COLS: usize>(ix: BoundedIx2<N_ROWS, N_COLS>) -> (f64, f64) {
+    (
+        MARGIN + ui::ROOM_SIZE * ix.x() as f64,
+        MARGIN + ui::ROOM_SIZE * ix.y() as f64,
+    )
+}
+
+fn centre<const N_ROWS: usize, const N_COLS: usize>(ix: BoundedIx2<N_ROWS, N_COLS>) -> (f64, f64) {
+    let (x, y) = corner(ix);
+    (x + ui::ROOM_SIZE / 2.0, y + ui::ROOM_SIZE / 2.0)
+}
+
+/// middle of the wall on side `dir` of a room
+fn edge<const N_ROWS: usize, const N_COLS: usize>(
+    ix: BoundedIx2<N_ROWS, N_COLS>,
+    dir: Direction,
+) -> (f64, f64) {
+    let (x, y) = centre(ix);
+    let (d_row, d_col) = dir.offset();
+    (
+        x + d_col as f64 * ui::ROOM_SIZE / 2.0,
+        y + d_row as f64 * ui::ROOM_SIZE / 2.0,
+    )
+}
+
+/// `color` as `#rrggbb`
+fn hex(color: Color, print: bool) -> String {
+    let (r, g, b) = format::rgb(color, print);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::test_maze;
+
+    #[test]
+    fn test_svg() {
+        let mut maze = test_maze(3);
+        maze.trail = vec![maze.current_ix];
+        let plain = to_svg(&maze, &SvgOptions::default());
+        assert!(plain.starts_with("<svg "));
+        assert!(plain.ends_with("</svg>\n"));
+        assert!(plain.contains("\u{1f600}"));
+        assert!(!plain.contains(&hex(SOLUTION_COLOR, false)));
+        let all = to_svg(
+            &maze,
+            &SvgOptions {
+                solution: true,
+                trail: true,
+                print: true,
+            },
+        );
+        // a step round the edge is drawn as two lines, one off each side
+        let path = maze.solution().unwrap();
+        let n_wrapped = path
+            .windows(2)
+            .filter(|pair| {
+                maze.moves_from(pair[0])
+                    .any(|(dir, next)| next == pair[1] && maze.wrap_doors(pair[0]).contains(&dir))
+            })
+            .count();
+        assert_eq!(
+            path.len() - 1 + n_wrapped,
+            all.matches(&hex(SOLUTION_COLOR, true)).count()
+        );
+        assert!(all.contains(&hex(ui::BREADCRUMB_COLOR, true)));
+        assert!(!all.contains(&hex(ui::WALL_COLOR, false)));
+    }
+}
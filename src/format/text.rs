@@ -0,0 +1,373 @@
+//! mazes as plain text, for writing them by hand and for looking at generated ones
+//!
+//! a maze is drawn as a grid of `+` corners with `--` and `|` walls, leaving a gap wherever a
+//! door is open. each room is two characters wide:
+//!
+//! ```text
+//! +--+--+--+
+//! |S    |##|
+//! +  +--+--+
+//! |  |1c G |
+//! +--+--+--+
+//! ```
+//!
+//! - the first character marks the start (`S`), the goal (`G`) or a checkpoint on the way
+//!   (`1` to `9`, reached in that order)
+//! - the second is whatever's lying there: `c` for a coin, `g` for a gem, `f` for fuel
+//! - `##` is empty space outside the maze, and `||` or `==` is a crossing where a north-south
+//!   or east-west corridor goes over the other one
+//!
+//! lines before the grid starting with `#` are comments, and a line saying `wrap` lets doors
+//! in the outside wall lead around to the opposite edge
+
+use crate::{
+    Direction,
+    goals::Goals,
+    mask::{self, Mask},
+    maze::{Axis, DoorState, Item, Maze, RoomKind},
+    save::Token,
+};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, eyre},
+};
+use multid::{BoundedIx2, iterators::V2Indices};
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+    str::FromStr,
+};
+
+/// directory maze files are loaded from
+pub const MAZE_DIR: &str = "mazes";
+/// most checkpoints a maze file can have, since each is marked with one digit
+const MAX_CHECKPOINTS: usize = 9;
+
+/// load `name` from the mazes directory
+pub fn load<const N_ROWS: usize, const N_COLS: usize>(name: &str) -> Result<Maze<N_ROWS, N_COLS>> {
+    let path = Path::new(MAZE_DIR).join(format!("{name}.txt"));
+    fs::read_to_string(&path)
+        .wrap_err_with(|| format!("couldn't read {}", path.display()))?
+        .parse()
+        .wrap_err_with(|| format!("couldn't load {}", path.display()))
+}
+
+/// names of the mazes in the mazes directory, in alphabetical order
+pub fn available() -> Vec<String> {
+    mask::txt_files(MAZE_DIR)
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Display for Maze<N_ROWS, N_COLS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.wrap {
+            writeln!(f, "wrap")?;
+        }
+        for row in 0..N_ROWS {
+            let mut walls = String::new();
+            let mut rooms = String::new();
+            for col in 0..N_COLS {
+                let ix = BoundedIx2::new(row, col).unwrap();
+                walls.push('+');
+                walls.push_str(if self.is_open(ix, Direction::North) {
+                    "  "
+                } else {
+                    "--"
+                });
+                rooms.push(if self.is_open(ix, Direction::West) {
+                    ' '
+                } else {
+                    '|'
+                });
+                rooms.push_str(&self.cell(ix));
+            }
+            walls.push('+');
+            let last = BoundedIx2::new(row, N_COLS - 1).unwrap();
+            rooms.push(if self.is_open(last, Direction::East) {
+                ' '
+            } else {
+                '|'
+            });
+            writeln!(f, "{walls}")?;
+            writeln!(f, "{}", rooms.trim_end())?;
+        }
+        for col in 0..N_COLS {
+            let ix = BoundedIx2::new(N_ROWS - 1, col).unwrap();
+            write!(
+                f,
+                "+{}",
+                if self.is_open(ix, Direction::South) {
+                    "  "
+                } else {
+                    "--"
+                }
+            )?;
+        }
+        writeln!(f, "+")
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Maze<N_ROWS, N_COLS> {
+    fn is_open(&self, ix: BoundedIx2<N_ROWS, N_COLS>, dir: Direction) -> bool {
+        self.rooms[ix].doors.get(dir) == Some(DoorState::Open)
+    }
+    /// the two characters inside a room
+    fn cell(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> String {
+        if !self.is_room(ix) {
+            return "##".into();
+        }
+        let room = &self.rooms[ix];
+        match room.kind {
+            RoomKind::Crossing {
+                over: Axis::NorthSouth,
+            } => return "||".into(),
+            RoomKind::Crossing {
+                over: Axis::EastWest,
+            } => return "==".into(),
+            RoomKind::Plain => (),
+        }
+        let marker = if ix == self.current_ix {
+            'S'
+        } else if ix == self.goals.last() {
+            'G'
+        } else {
+            match self.goals.position(ix) {
+                Some(n) => char::from_digit(n as u32 + 1, 10).unwrap_or('?'),
+                None => ' ',
+            }
+        };
+        let item = room
+            .item
+            .map_or(' ', |item| item.token().chars().next().unwrap());
+        format!("{marker}{item}")
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> FromStr for Maze<N_ROWS, N_COLS> {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut wrap = false;
+        let mut lines = s.lines().peekable();
+        while let Some(line) = lines.next_if(|l| !l.trim_start().starts_with('+')) {
+            match line.trim() {
+                "" => (),
+                "wrap" => wrap = true,
+                l if l.starts_with('#') => (),
+                l => bail!("expected a comment, `wrap` or the top of the maze, got {l:?}"),
+            }
+        }
+        let width = 3 * N_COLS + 1;
+        let grid: Vec<Vec<char>> = lines
+            .map(str::trim_end)
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                let mut chars: Vec<char> = l.chars().collect();
+                chars.resize(chars.len().max(width), ' ');
+                chars
+            })
+            .collect();
+        if grid.len() != 2 * N_ROWS + 1 {
+            bail!(
+                "expected {} lines for {N_ROWS} rows, got {}",
+                2 * N_ROWS + 1,
+                grid.len()
+            );
+        }
+        if let Some(n) = grid.iter().position(|l| l.len() != width) {
+            bail!("line {n} of the maze is wider than {N_COLS} rooms");
+        }
+        // the character(s) in the wall on side `dir` of the room at (row, col)
+        let wall = |row: usize, col: usize, dir: Direction| -> String {
+            let (y, x) = (2 * row + 1, 3 * col);
+            match dir {
+                Direction::North => grid[y - 1][x + 1..x + 3].iter().collect(),
+                Direction::South => grid[y + 1][x + 1..x + 3].iter().collect(),
+                Direction::West => grid[y][x].to_string(),
+                Direction::East => grid[y][x + 3].to_string(),
+            }
+        };
+        let gap = |row: usize, col: usize, dir: Direction| -> Result<bool> {
+            match wall(row, col, dir).trim() {
+                "" => Ok(true),
+                "-" | "--" | "|" => Ok(false),
+                other => bail!("row {row}, column {col}: {other:?} isn't a wall"),
+            }
+        };
+        let cell = |row: usize, col: usize| -> [char; 2] {
+            let (y, x) = (2 * row + 1, 3 * col);
+            [grid[y][x + 1], grid[y][x + 2]]
+        };
+        let mask_text: Vec<String> = (0..N_ROWS)
+            .map(|row| {
+                (0..N_COLS)
+                    .map(|col| {
+                        if cell(row, col) == ['#', '#'] {
+                            '.'
+                        } else {
+                            '#'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let mask: Mask<N_ROWS, N_COLS> = mask_text.join("\n").parse()?;
+        let mut maze = Maze::with_mask(mask, wrap);
+        let mut start = None;
+        let mut goal = None;
+        let mut checkpoints = [None; MAX_CHECKPOINTS];
+        for ix in V2Indices::<N_ROWS, N_COLS>::new() {
+            let (row, col) = (ix.y(), ix.x());
+            let at = || format!("row {row}, column {col}");
+            for dir in [
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ] {
+                let open = gap(row, col, dir)?;
+                // in a wrapping maze the two edges are drawn as the same wall
+                if maze.is_room(ix)
+                    && let Some(next) = maze.neighbor(ix, dir)
+                    && open != gap(next.y(), next.x(), dir.opposite())?
+                {
+                    bail!(
+                        "{}: the wall to the {dir:?} isn't the same from both sides",
+                        at()
+                    );
+                }
+                match (maze.rooms[ix].doors.get(dir), open) {
+                    (Some(_), true) => maze.open_dir(ix, dir),
+                    (None, true) if maze.is_room(ix) => {
+                        bail!("{}: there's a gap in the wall to the {dir:?}", at())
+                    }
+                    _ => (),
+                }
+            }
+            if !maze.is_room(ix) {
+                continue;
+            }
+            match cell(row, col) {
+                ['|', '|'] | ['=', '='] => {
+                    let over = if cell(row, col)[0] == '|' {
+                        Axis::NorthSouth
+                    } else {
+                        Axis::EastWest
+                    };
+                    maze.rooms[ix].kind = RoomKind::Crossing { over };
+                }
+                [marker, item] => {
+                    match marker {
+                        ' ' => (),
+                        'S' if start.is_none() => start = Some(ix),
+                        'G' if goal.is_none() => goal = Some(ix),
+                        'S' | 'G' => bail!("{}: there's more than one {marker}", at()),
+                        '1'..='9' => {
+                            let n = marker.to_digit(10).unwrap() as usize - 1;
+                            if checkpoints[n].replace(ix).is_some() {
+                                bail!("{}: there's more than one checkpoint {marker}", at());
+                            }
+                        }
+                        other => bail!("{}: {other:?} isn't S, G or a checkpoint", at()),
+                    }
+                    maze.rooms[ix].item = match item {
+                        ' ' => None,
+                        other => Some(
+                            Item::from_token(&other.to_string())
+                                .ok_or_else(|| eyre!("{}: {other:?} isn't an item", at()))?,
+                        ),
+                    };
+                }
+            }
+        }
+        maze.current_ix = start.ok_or_else(|| eyre!("there's no start (S)"))?;
+        maze.goals = Goals::single(goal.ok_or_else(|| eyre!("there's no goal (G)"))?);
+        let n_checkpoints = checkpoints.iter().take_while(|c| c.is_some()).count();
+        if checkpoints[n_checkpoints..].iter().any(Option::is_some) {
+            bail!("checkpoints have to be numbered from 1 with none missing");
+        }
+        for ix in checkpoints.into_iter().flatten() {
+            maze.goals.insert_checkpoint(ix);
+        }
+        maze.check()?;
+        Ok(maze)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::test_maze;
+
+    const SMALL: &str = "\
+# a comment
++--+--+--+
+|S    |##|
++  +--+--+
+|  |1c G |
++--+--+--+
+";
+
+    #[test]
+    fn test_parse() {
+        let maze: Maze<2, 3> = SMALL.parse().unwrap();
+        let ix = |row, col| BoundedIx2::new(row, col).unwrap();
+        assert_eq!(ix(0, 0), maze.current_ix);
+        assert_eq!(
+            vec![ix(1, 1), ix(1, 2)],
+            maze.goals.iter().collect::<Vec<_>>()
+        );
+        assert!(!maze.is_room(ix(0, 2)));
+        assert_eq!(Some(Item::Coin), maze.rooms[ix(1, 1)].item);
+        assert!(maze.is_open(ix(0, 0), Direction::East));
+        assert!(maze.is_open(ix(0, 1), Direction::West));
+        assert!(!maze.is_open(ix(0, 1), Direction::East));
+        assert!(maze.is_open(ix(0, 0), Direction::South));
+        assert!(!maze.is_open(ix(1, 0), Direction::East));
+        assert_eq!(SMALL.replace("# a comment\n", ""), maze.to_string());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for seed in 0..20 {
+            let maze = test_maze(seed);
+            let text = maze.to_string();
+            let parsed: Maze<7, 7> = text.parse().unwrap();
+            assert_eq!(text, parsed.to_string());
+            assert_eq!(format!("{:?}", maze.rooms), format!("{:?}", parsed.rooms));
+        }
+    }
+
+    #[test]
+    fn test_bundled_mazes() {
+        for name in available() {
+            assert!(load::<7, 7>(&name).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_bad_mazes() {
+        let no_goal = SMALL.replace('G', " ");
+        assert!(no_goal.parse::<Maze<2, 3>>().is_err());
+        let gap_in_border = SMALL.replace("|S", " S");
+        assert!(gap_in_border.parse::<Maze<2, 3>>().is_err());
+        assert!(SMALL.parse::<Maze<3, 3>>().is_err());
+        let walled_in_crossing = "\
++--+--+--+
+|S       |
++--+--+--+
+|  |||   |
++--+--+--+
+|G       |
++--+--+--+
+";
+        assert!(walled_in_crossing.parse::<Maze<3, 3>>().is_err());
+        let wrap_gap = format!("wrap\n{}", SMALL.replacen("+--+--+--+", "+  +--+--+", 1));
+        assert!(
+            wrap_gap.parse::<Maze<2, 3>>().is_err(),
+            "gap at the top but not the bottom"
+        );
+        let both = format!("wrap\n{}", SMALL.replace("+--+--+--+", "+  +--+--+"));
+        assert!(both.parse::<Maze<2, 3>>().is_ok());
+    }
+}
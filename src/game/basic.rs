@@ -1,46 +1,63 @@
-use super::Outcome;
+use super::{Outcome, grid_trail, mode::GameMode, new_seeded, options::Options};
 use crate::{
     maze::Maze,
     movement::MazeEvent,
-    ui::{self, RoomView},
+    save::{Reader, Save, Token, Writer},
+    ui::{self, Breadcrumb, RoomView, TrailView},
 };
 use color_eyre::Result;
-use crossterm::event;
-use multid::iterators::V2Indices;
+use rand::rngs::StdRng;
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame,
     buffer::Buffer,
     layout::Rect,
     widgets::{StatefulWidget, Widget, canvas::Canvas},
 };
 
-pub struct BasicGame<const N_ROWS: usize, const N_COLS: usize>;
+pub struct BasicGame<const N_ROWS: usize, const N_COLS: usize> {
+    trail: TrailView,
+}
 
 impl<const N_ROWS: usize, const N_COLS: usize> StatefulWidget for BasicGame<N_ROWS, N_COLS> {
     type State = Maze<N_ROWS, N_COLS>;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let max_visits = state.max_visits();
         let c = Canvas::default()
             .x_bounds([ui::MIN_X, ui::MAX_X])
             .y_bounds([ui::MIN_Y, ui::MAX_Y])
             .background_color(ui::BG_COLOR)
             .paint(move |ctx| {
-                for ix in V2Indices::<N_ROWS, N_COLS>::new() {
+                for ix in state.indices() {
                     let room = &state.rooms[ix];
+                    let x = -200.0 + ui::ROOM_SIZE * ix.x() as f64;
+                    let y = 200.0 - ui::ROOM_SIZE * ix.y() as f64;
                     let view = RoomView {
-                        x: -200.0 + ui::ROOM_SIZE * ix.x() as f64,
-                        y: 200.0 - ui::ROOM_SIZE * ix.y() as f64,
+                        x,
+                        y,
                         room,
+                        wrap_doors: state.wrap_doors(ix),
+                        fade: 0.0,
+                        marks: None,
                     };
                     ctx.draw(&view);
+                    ctx.draw(&Breadcrumb {
+                        x,
+                        y,
+                        visits: state.visits(ix),
+                        max_visits,
+                        view: self.trail,
+                    });
                     let label_x = -200.0 + (ui::ROOM_SIZE * ix.x() as f64) + ui::SEG_LEN * 3.5;
                     let label_y = 200.0 - (ui::ROOM_SIZE * ix.y() as f64 + ui::SEG_LEN * 3.5);
-                    if ix == state.current_ix && ix == state.goal {
+                    if ix == state.current_ix && state.goals.contains(ix) {
                         ctx.print(label_x, label_y, "\u{1f940}")
                     } else if ix == state.current_ix {
                         ctx.print(label_x, label_y, "\u{1f600}")
-                    } else if ix == state.goal {
-                        ctx.print(label_x, label_y, "\u{1f945}")
+                    } else if let Some(label) = ui::goal_label(&state.goals, ix) {
+                        ctx.print(label_x, label_y, label)
+                    } else if let Some(item) = room.item {
+                        ctx.print(label_x, label_y, ui::item_label(item))
                     }
                 }
             });
@@ -48,24 +65,63 @@ impl<const N_ROWS: usize, const N_COLS: usize> StatefulWidget for BasicGame<N_RO
     }
 }
 
-pub fn game<const N_ROWS: usize, const N_COLS: usize>(
-    terminal: &mut DefaultTerminal,
-    maze: &mut Maze<N_ROWS, N_COLS>,
-) -> Result<Outcome> {
-    loop {
-        terminal.draw(|frame: &mut Frame| {
-            frame.render_stateful_widget(BasicGame {}, frame.area(), maze)
-        })?;
-        if maze.is_done() {
-            return Ok(Outcome::Win);
-        }
-        match event::read()?.into() {
-            MazeEvent::MoveN => maze.move_north(),
-            MazeEvent::MoveS => maze.move_south(),
-            MazeEvent::MoveE => maze.move_east(),
-            MazeEvent::MoveW => maze.move_west(),
-            MazeEvent::Quit => return Ok(Outcome::Quit),
+/// a maze with nothing hidden
+pub struct BasicMode<const N_ROWS: usize, const N_COLS: usize> {
+    maze: Maze<N_ROWS, N_COLS>,
+    trail: TrailView,
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> GameMode for BasicMode<N_ROWS, N_COLS> {
+    fn handle_event(&mut self, event: MazeEvent) {
+        match event {
+            MazeEvent::MoveN => self.maze.move_north(),
+            MazeEvent::MoveS => self.maze.move_south(),
+            MazeEvent::MoveE => self.maze.move_east(),
+            MazeEvent::MoveW => self.maze.move_west(),
+            MazeEvent::Undo => self.maze.undo(),
             _ => false,
         };
     }
+    fn render(&mut self, frame: &mut Frame) {
+        let [maze_area, hud_area] = ui::hud_areas(frame.area());
+        frame.render_stateful_widget(BasicGame { trail: self.trail }, maze_area, &mut self.maze);
+        frame.render_widget(
+            ui::Hud {
+                items: self.maze.status(),
+            },
+            hud_area,
+        );
+    }
+    fn outcome(&self) -> Option<Outcome> {
+        self.maze.is_done().then_some(Outcome::Win)
+    }
+    fn trail(&self) -> ([&'static str; 2], Vec<(usize, usize)>) {
+        grid_trail(&self.maze)
+    }
+    fn position(&self) -> (usize, usize) {
+        (self.maze.current_ix.y(), self.maze.current_ix.x())
+    }
+    fn save(&self, out: &mut Writer) {
+        self.maze.save(out);
+        out.field("trail_view", self.trail.token());
+    }
+}
+
+pub fn new_game<const N_ROWS: usize, const N_COLS: usize>(
+    rng: &mut StdRng,
+    options: &Options,
+) -> Result<Box<dyn GameMode>> {
+    Ok(Box::new(BasicMode {
+        maze: new_seeded::<N_ROWS, N_COLS>(rng, options)?,
+        trail: options.trail,
+    }))
+}
+
+pub fn load<const N_ROWS: usize, const N_COLS: usize>(
+    input: &mut Reader,
+) -> Result<Box<dyn GameMode>> {
+    Ok(Box::new(BasicMode {
+        maze: Maze::<N_ROWS, N_COLS>::load(input)?,
+        trail: input.token("trail_view")?,
+    }))
 }
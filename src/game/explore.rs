@@ -0,0 +1,162 @@
+use crate::{
+    Direction,
+    chalk::Chalk,
+    maze::Maze,
+    movement::MazeEvent,
+    save::{self, Reader, Save, Writer},
+};
+use color_eyre::{Result, eyre::bail};
+use multid::BoundedIx2;
+use std::collections::BTreeMap;
+
+/// what the player has seen and chalked while finding their way around a maze they can't see
+/// all of
+#[derive(Debug, Clone)]
+pub struct Explorer<const N_ROWS: usize, const N_COLS: usize> {
+    /// the move each room was last seen on
+    seen: BTreeMap<BoundedIx2<N_ROWS, N_COLS>, usize>,
+    moves: usize,
+    /// moves it takes to forget a room, if the player forgets at all
+    memory: Option<usize>,
+    pub chalk: Chalk<N_ROWS, N_COLS>,
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Explorer<N_ROWS, N_COLS> {
+    pub fn new(memory: Option<usize>, chalk_limit: Option<usize>) -> Self {
+        Self {
+            seen: BTreeMap::new(),
+            moves: 0,
+            memory,
+            chalk: Chalk::new(chalk_limit),
+        }
+    }
+    /// move, undo or chalk a mark; says whether the player ended up in another room. undoing
+    /// counts as a move, so it doesn't stop the player forgetting, and rooms seen before the undo
+    /// stay seen
+    pub fn handle_event(&mut self, maze: &mut Maze<N_ROWS, N_COLS>, event: MazeEvent) -> bool {
+        let moved = match event {
+            MazeEvent::MoveN => maze.move_north(),
+            MazeEvent::MoveS => maze.move_south(),
+            MazeEvent::MoveE => maze.move_east(),
+            MazeEvent::MoveW => maze.move_west(),
+            MazeEvent::Undo => maze.undo(),
+            MazeEvent::Mark => {
+                self.chalk.mark_room(maze.current_ix);
+                false
+            }
+            MazeEvent::MarkDoor(dir) => {
+                self.mark_door(maze, dir);
+                false
+            }
+            _ => false,
+        };
+        if moved {
+            self.moves += 1;
+        }
+        moved
+    }
+    fn mark_door(&mut self, maze: &Maze<N_ROWS, N_COLS>, dir: Direction) {
+        let ix = maze.current_ix;
+        if maze.rooms[ix].doors.get(dir).is_some() {
+            self.chalk.mark_door(ix, dir);
+        }
+    }
+    /// see the current room, and any crossings the player just went over or under
+    pub fn look(&mut self, maze: &Maze<N_ROWS, N_COLS>) {
+        self.seen.insert(maze.current_ix, self.moves);
+        for ix in maze.crossings_from(maze.current_ix) {
+            self.seen.insert(ix, self.moves);
+        }
+    }
+    /// how far `ix` has faded from memory, or `None` if it's never been seen or is forgotten
+    pub fn fade(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> Option<f64> {
+        let age = self.moves - self.seen.get(&ix)?;
+        match self.memory {
+            None => Some(0.0),
+            Some(memory) if age >= memory => None,
+            Some(memory) => Some(age as f64 / memory as f64),
+        }
+    }
+    pub fn is_seen(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> bool {
+        self.fade(ix).is_some()
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Save for Explorer<N_ROWS, N_COLS> {
+    /// each room seen as `row,col,move`
+    fn save(&self, out: &mut Writer) {
+        out.list(
+            "seen",
+            self.seen
+                .iter()
+                .map(|(ix, seen)| format!("{},{seen}", save::ix(*ix))),
+        );
+        out.field("moves", self.moves);
+        out.field("memory", save::optional(self.memory));
+        self.chalk.save(out);
+    }
+    fn load(input: &mut Reader) -> Result<Self> {
+        let seen = input.list("seen", |s| {
+            let (ix, seen) = save::parse_ix(s)?;
+            Some((ix, seen.parse().ok()?))
+        })?;
+        let moves = input.parse("moves")?;
+        if let Some((ix, seen)) = seen.iter().find(|(_, seen)| *seen > moves) {
+            bail!("room {ix} was seen on move {seen}, but there have only been {moves}");
+        }
+        Ok(Self {
+            seen: seen.into_iter().collect(),
+            moves,
+            memory: input.optional("memory")?,
+            chalk: Chalk::load(input)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_forget() {
+        let mut maze = Maze::<1, 3>::new();
+        maze.open_east(BoundedIx2::new(0, 0).unwrap());
+        maze.open_east(BoundedIx2::new(0, 1).unwrap());
+        let start = maze.current_ix;
+        let mut explorer = Explorer::new(Some(2), None);
+        explorer.look(&maze);
+        explorer.handle_event(&mut maze, MazeEvent::MoveE);
+        explorer.look(&maze);
+        assert_eq!(Some(0.5), explorer.fade(start));
+        explorer.handle_event(&mut maze, MazeEvent::MoveE);
+        explorer.look(&maze);
+        assert_eq!(None, explorer.fade(start), "forgotten");
+        explorer.handle_event(&mut maze, MazeEvent::MoveW);
+        explorer.look(&maze);
+        assert_eq!(Some(0.0), explorer.fade(maze.current_ix));
+        let middle = maze.current_ix;
+        explorer.handle_event(&mut maze, MazeEvent::Undo);
+        explorer.look(&maze);
+        assert_eq!(BoundedIx2::new(0, 2).unwrap(), maze.current_ix);
+        assert_eq!(
+            (Some(0.0), Some(0.5)),
+            (explorer.fade(maze.current_ix), explorer.fade(middle)),
+            "undoing counts as a move"
+        );
+    }
+    #[test]
+    fn test_load() {
+        let mut explorer = Explorer::<1, 3>::new(Some(2), None);
+        explorer.seen.insert(BoundedIx2::new(0, 1).unwrap(), 3);
+        explorer.moves = 3;
+        let mut out = Writer::default();
+        explorer.save(&mut out);
+        let saved = out.finish();
+        assert!(Explorer::<1, 3>::load(&mut Reader::new(&saved)).is_ok());
+        let ahead = saved.replace("moves 3", "moves 2");
+        assert!(
+            Explorer::<1, 3>::load(&mut Reader::new(&ahead)).is_err(),
+            "seen after the last move"
+        );
+    }
+}
@@ -1,26 +1,25 @@
-use super::Outcome;
+use super::{Outcome, explore::Explorer, grid_trail, mode::GameMode, new_seeded, options::Options};
 use crate::{
-    Direction,
     maze::Maze,
     movement::MazeEvent,
-    ui::{self, RoomView, UnseenRoomView},
+    save::{Reader, Save, Token, Writer},
+    ui::{self, TrailView},
 };
 use color_eyre::Result;
-use crossterm::event;
-use multid::{BoundedIx2, iterators::V2Indices};
+use rand::rngs::StdRng;
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame,
     buffer::Buffer,
     layout::Rect,
-    widgets::{StatefulWidget, Widget, canvas::Canvas},
+    widgets::{StatefulWidget, Widget},
 };
-use std::{collections::BTreeSet, marker::PhantomData};
+use std::marker::PhantomData;
 
-pub struct HiddenGame<'a, const N_ROWS: usize, const N_COLS: usize> {
-    _marker: PhantomData<&'a mut Maze<N_ROWS, N_COLS>>,
+pub struct HiddenGame<const N_ROWS: usize, const N_COLS: usize> {
+    _marker: PhantomData<Maze<N_ROWS, N_COLS>>,
 }
 
-impl<'a, const N_ROWS: usize, const N_COLS: usize> HiddenGame<'a, N_ROWS, N_COLS> {
+impl<const N_ROWS: usize, const N_COLS: usize> HiddenGame<N_ROWS, N_COLS> {
     fn new() -> Self {
         Self {
             _marker: PhantomData,
@@ -28,114 +27,74 @@ impl<'a, const N_ROWS: usize, const N_COLS: usize> HiddenGame<'a, N_ROWS, N_COLS
     }
 }
 
-pub struct HiddenGameState<'a, const N_ROWS: usize, const N_COLS: usize> {
-    maze: &'a mut Maze<N_ROWS, N_COLS>,
-    seen: BTreeSet<BoundedIx2<N_ROWS, N_COLS>>,
+pub struct HiddenGameState<const N_ROWS: usize, const N_COLS: usize> {
+    maze: Maze<N_ROWS, N_COLS>,
+    explorer: Explorer<N_ROWS, N_COLS>,
+    trail: TrailView,
 }
 
-impl<'a, const N_ROWS: usize, const N_COLS: usize> HiddenGameState<'a, N_ROWS, N_COLS> {
-    fn move_north(&mut self) {
-        self.maze.move_north();
+impl<const N_ROWS: usize, const N_COLS: usize> StatefulWidget for HiddenGame<N_ROWS, N_COLS> {
+    type State = HiddenGameState<N_ROWS, N_COLS>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        ui::ExploredMap {
+            maze: &state.maze,
+            fade: |ix| state.explorer.fade(ix),
+            all_goals: true,
+            trail: state.trail,
+            chalk: &state.explorer.chalk,
+        }
+        .render(area, buf);
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> GameMode for HiddenGameState<N_ROWS, N_COLS> {
+    fn handle_event(&mut self, event: MazeEvent) {
+        self.explorer.handle_event(&mut self.maze, event);
     }
-    fn move_east(&mut self) {
-        self.maze.move_east();
+    fn update(&mut self) {
+        self.explorer.look(&self.maze);
     }
-    fn move_south(&mut self) {
-        self.maze.move_south();
+    fn render(&mut self, frame: &mut Frame) {
+        let [maze_area, hud_area] = ui::hud_areas(frame.area());
+        frame.render_stateful_widget(HiddenGame::new(), maze_area, self);
+        let mut items = self.maze.status();
+        items.extend(self.explorer.chalk.status());
+        frame.render_widget(ui::Hud { items }, hud_area);
     }
-    fn move_west(&mut self) {
-        self.maze.move_west();
+    fn outcome(&self) -> Option<Outcome> {
+        self.maze.is_done().then_some(Outcome::Win)
     }
-    fn insert_current_ix(&mut self) {
-        self.seen.insert(self.maze.current_ix);
+    fn trail(&self) -> ([&'static str; 2], Vec<(usize, usize)>) {
+        grid_trail(&self.maze)
     }
-    fn is_done(&self) -> bool {
-        self.maze.is_done()
+    fn position(&self) -> (usize, usize) {
+        (self.maze.current_ix.y(), self.maze.current_ix.x())
     }
-    fn is_seen(&self, ix: &BoundedIx2<N_ROWS, N_COLS>) -> bool {
-        self.seen.contains(ix)
+    fn save(&self, out: &mut Writer) {
+        self.maze.save(out);
+        self.explorer.save(out);
+        out.field("trail_view", self.trail.token());
     }
 }
 
-impl<'a, const N_ROWS: usize, const N_COLS: usize> StatefulWidget
-    for HiddenGame<'a, N_ROWS, N_COLS>
-{
-    type State = HiddenGameState<'a, N_ROWS, N_COLS>;
-
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let c = Canvas::default()
-            .x_bounds([ui::MIN_X, ui::MAX_X])
-            .y_bounds([ui::MIN_Y, ui::MAX_Y])
-            .background_color(ui::BG_COLOR)
-            .paint(move |ctx| {
-                for ix in V2Indices::<N_ROWS, N_COLS>::new() {
-                    let x = -200.0 + ui::ROOM_SIZE * ix.x() as f64;
-                    let y = 200.0 - ui::ROOM_SIZE * ix.y() as f64;
-                    let label_x = -200.0 + (ui::ROOM_SIZE * ix.x() as f64) + ui::SEG_LEN * 3.5;
-                    let label_y = 200.0 - (ui::ROOM_SIZE * ix.y() as f64 + ui::SEG_LEN * 3.5);
-                    if ix == state.maze.goal {
-                        ctx.print(label_x, label_y, "\u{1f945}")
-                    };
-                    if state.is_seen(&ix) {
-                        let room = &state.maze.rooms[ix];
-                        let view = RoomView { x, y, room };
-                        ctx.draw(&view);
-                        if ix == state.maze.current_ix && ix == state.maze.goal {
-                            ctx.print(label_x, label_y, "\u{1f940}")
-                        } else if ix == state.maze.current_ix {
-                            ctx.print(label_x, label_y, "\u{1f600}")
-                        } else if ix == state.maze.goal {
-                            ctx.print(label_x, label_y, "\u{1f945}")
-                        }
-                    } else {
-                        let mut unseen: Vec<Direction> = Vec::with_capacity(4);
-                        if ix.north().map(|i| !state.seen.contains(&i)).unwrap_or(true) {
-                            unseen.push(Direction::North);
-                        }
-                        if ix.south().map(|i| !state.seen.contains(&i)).unwrap_or(true) {
-                            unseen.push(Direction::South);
-                        }
-                        if ix.east().map(|i| !state.seen.contains(&i)).unwrap_or(true) {
-                            unseen.push(Direction::East);
-                        }
-                        if ix.west().map(|i| !state.seen.contains(&i)).unwrap_or(true) {
-                            unseen.push(Direction::West);
-                        }
-                        ctx.draw(&UnseenRoomView {
-                            x,
-                            y,
-                            hidden_walls: unseen,
-                        });
-                    }
-                }
-            });
-        Widget::render(c, area, buf);
-    }
+pub fn new_game<const N_ROWS: usize, const N_COLS: usize>(
+    rng: &mut StdRng,
+    options: &Options,
+) -> Result<Box<dyn GameMode>> {
+    Ok(Box::new(HiddenGameState {
+        maze: new_seeded::<N_ROWS, N_COLS>(rng, options)?,
+        explorer: Explorer::new(options.memory, options.chalk),
+        trail: options.trail,
+    }))
 }
 
-pub fn game<const N_ROWS: usize, const N_COLS: usize>(
-    terminal: &mut DefaultTerminal,
-    maze: &mut Maze<N_ROWS, N_COLS>,
-) -> Result<Outcome> {
-    let mut st: HiddenGameState<N_ROWS, N_COLS> = HiddenGameState {
-        maze,
-        seen: BTreeSet::new(),
-    };
-    loop {
-        st.insert_current_ix();
-        terminal.draw(|frame: &mut Frame| {
-            frame.render_stateful_widget(HiddenGame::new(), frame.area(), &mut st)
-        })?;
-        if st.is_done() {
-            return Ok(Outcome::Win);
-        }
-        match event::read()?.into() {
-            MazeEvent::MoveN => &st.move_north(),
-            MazeEvent::MoveS => &st.move_south(),
-            MazeEvent::MoveE => &st.move_east(),
-            MazeEvent::MoveW => &st.move_west(),
-            MazeEvent::Quit => return Ok(Outcome::Quit),
-            _ => &(),
-        };
-    }
+pub fn load<const N_ROWS: usize, const N_COLS: usize>(
+    input: &mut Reader,
+) -> Result<Box<dyn GameMode>> {
+    Ok(Box::new(HiddenGameState {
+        maze: Maze::<N_ROWS, N_COLS>::load(input)?,
+        explorer: Explorer::load(input)?,
+        trail: input.token("trail_view")?,
+    }))
 }
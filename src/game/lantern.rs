@@ -1,152 +1,376 @@
-use super::Outcome;
+use super::{
+    Outcome, explore::Explorer, grid_trail, mode::GameMode, new_seeded, options::Options,
+    scatter_fuel,
+};
 use crate::{
-    Direction,
-    maze::Maze,
+    DirectionsIter,
+    maze::{DoorState, Item, Maze},
     movement::MazeEvent,
-    ui::{self, RoomView, UnseenRoomView},
+    save::{self, Reader, Save, Token, Writer},
+    ui::{self, Breadcrumb, RoomView, TrailView, UnseenRoomView},
 };
 use color_eyre::Result;
-use crossterm::event;
-use multid::{BoundedIx2, iterators::Ix2Neighbors};
+use crossterm::event::KeyCode;
+use multid::BoundedIx2;
+use rand::rngs::StdRng;
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame,
     buffer::Buffer,
-    layout::Rect,
+    layout::{Constraint, Layout, Rect},
     widgets::{StatefulWidget, Widget, canvas::Canvas},
 };
 use std::{collections::BTreeSet, marker::PhantomData};
 
-pub struct LanternGame<'a, const N_ROWS: usize, const N_COLS: usize> {
-    _marker: PhantomData<&'a mut Maze<N_ROWS, N_COLS>>,
+/// moves a full lantern's worth of radius lasts for
+const FUEL_PER_RADIUS: usize = 10;
+/// fuel in each can lying around the maze
+const REFUEL_AMOUNT: usize = 20;
+/// cans of fuel scattered around the maze when the lantern needs it
+pub const REFUEL_PICKUPS: usize = 4;
+/// choices for how much fuel the lantern starts with; `None` never runs out
+pub const FUEL_CHOICES: [Option<usize>; 4] = [None, Some(30), Some(60), Some(100)];
+/// biggest radius the menu offers
+pub const MAX_RADIUS: usize = 2;
+/// furthest the player can see down a corridor; any more and it goes off the edge of the screen
+const MAX_SIGHT: usize = 3;
+
+/// which rooms count as within reach of the light
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum LightShape {
+    /// every room within `radius` rows and columns
+    #[default]
+    Square,
+    /// rooms within `radius` steps, walls or no walls
+    Manhattan,
+    /// rooms within `radius` steps through open doors
+    Doors,
+    /// straight down each corridor until a wall gets in the way, however far the radius is
+    LineOfSight,
 }
 
-impl<'a, const N_ROWS: usize, const N_COLS: usize> LanternGame<'a, N_ROWS, N_COLS> {
-    fn new() -> Self {
-        Self {
-            _marker: PhantomData,
+impl LightShape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LightShape::Square => "square",
+            LightShape::Manhattan => "diamond",
+            LightShape::Doors => "through open doors",
+            LightShape::LineOfSight => "line of sight",
         }
     }
 }
 
-pub struct LanternGameState<'a, const N_ROWS: usize, const N_COLS: usize> {
-    maze: &'a mut Maze<N_ROWS, N_COLS>,
-    seen: BTreeSet<BoundedIx2<N_ROWS, N_COLS>>,
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Lantern {
+    /// how many rooms away the light reaches with plenty of fuel
+    pub radius: usize,
+    pub shape: LightShape,
+    /// moves left before the lantern goes out, if it burns fuel at all
+    pub fuel: Option<usize>,
 }
 
-impl<'a, const N_ROWS: usize, const N_COLS: usize> LanternGameState<'a, N_ROWS, N_COLS> {
-    fn move_north(&mut self) {
-        self.maze.move_north();
+impl Default for Lantern {
+    fn default() -> Self {
+        Self {
+            radius: 1,
+            shape: LightShape::default(),
+            fuel: None,
+        }
     }
-    fn move_east(&mut self) {
-        self.maze.move_east();
+}
+
+impl Lantern {
+    /// how far the light reaches right now; it shrinks as the fuel runs low, down to just the
+    /// current room once it's gone
+    pub fn radius(&self) -> usize {
+        self.fuelled(self.radius)
     }
-    fn move_south(&mut self) {
-        self.maze.move_south();
+    /// `full` cut down to however far the fuel left lets the light reach
+    fn fuelled(&self, full: usize) -> usize {
+        match self.fuel {
+            None => full,
+            Some(fuel) => full.min(fuel.div_ceil(FUEL_PER_RADIUS)),
+        }
     }
-    fn move_west(&mut self) {
-        self.maze.move_west();
+    /// use up a move's worth of fuel, and top up if the move picked up a can
+    pub fn burn(&mut self, pickup: Option<Item>) {
+        if let Some(fuel) = &mut self.fuel {
+            *fuel = fuel.saturating_sub(1);
+            if pickup == Some(Item::Fuel) {
+                *fuel += REFUEL_AMOUNT;
+            }
+        }
     }
-    fn insert_current_ix(&mut self) {
-        self.seen.insert(self.maze.current_ix);
+    /// (row, column) offsets from the current room of every room the lantern lights up, with the
+    /// current room last so it's drawn on top
+    pub fn lit<const N_ROWS: usize, const N_COLS: usize>(
+        &self,
+        maze: &Maze<N_ROWS, N_COLS>,
+    ) -> Vec<((isize, isize), BoundedIx2<N_ROWS, N_COLS>)> {
+        let radius = self.radius() as isize;
+        let curr_ix = maze.current_ix;
+        let mut lit = Vec::new();
+        match self.shape {
+            LightShape::Square | LightShape::Manhattan => {
+                for d_rows in -radius..=radius {
+                    for d_cols in -radius..=radius {
+                        if (d_rows, d_cols) == (0, 0)
+                            || (self.shape == LightShape::Manhattan
+                                && d_rows.abs() + d_cols.abs() > radius)
+                        {
+                            continue;
+                        }
+                        if let Some(ix) = maze
+                            .offset(curr_ix, d_rows, d_cols)
+                            .filter(|ix| maze.is_room(*ix))
+                        {
+                            lit.push(((d_rows, d_cols), ix));
+                        }
+                    }
+                }
+            }
+            LightShape::Doors => {
+                let mut reached: BTreeSet<(isize, isize)> = BTreeSet::from([(0, 0)]);
+                let mut frontier = vec![((0, 0), curr_ix)];
+                for _ in 0..radius {
+                    let mut next_frontier = Vec::new();
+                    for ((d_rows, d_cols), ix) in frontier {
+                        for dir in DirectionsIter::new() {
+                            if maze.rooms[ix].doors.get(dir) != Some(DoorState::Open) {
+                                continue;
+                            }
+                            let Some(next) = maze.neighbor(ix, dir) else {
+                                continue;
+                            };
+                            let (step_rows, step_cols) = dir.offset();
+                            let offset = (d_rows + step_rows, d_cols + step_cols);
+                            if reached.insert(offset) {
+                                lit.push((offset, next));
+                                next_frontier.push((offset, next));
+                            }
+                        }
+                    }
+                    frontier = next_frontier;
+                }
+            }
+            LightShape::LineOfSight => {
+                for (dir, dist, ix) in maze.sight_lines(curr_ix, self.fuelled(MAX_SIGHT)) {
+                    let (step_rows, step_cols) = dir.offset();
+                    lit.push(((step_rows * dist as isize, step_cols * dist as isize), ix));
+                }
+            }
+        }
+        lit.push(((0, 0), curr_ix));
+        lit
+    }
+}
+
+impl Save for Lantern {
+    fn save(&self, out: &mut Writer) {
+        out.field("light_radius", self.radius);
+        out.field("light_shape", self.shape.token());
+        out.field("fuel", save::optional(self.fuel));
     }
-    fn is_done(&self) -> bool {
-        self.maze.is_done()
+    fn load(input: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            radius: input.parse("light_radius")?,
+            shape: input.token("light_shape")?,
+            fuel: input.optional("fuel")?,
+        })
     }
-    fn is_seen(&self, ix: &BoundedIx2<N_ROWS, N_COLS>) -> bool {
-        self.seen.contains(ix)
+}
+
+pub struct LanternGame<const N_ROWS: usize, const N_COLS: usize> {
+    _marker: PhantomData<Maze<N_ROWS, N_COLS>>,
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> LanternGame<N_ROWS, N_COLS> {
+    fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
     }
 }
 
-impl<'a, const N_ROWS: usize, const N_COLS: usize> StatefulWidget
-    for LanternGame<'a, N_ROWS, N_COLS>
-{
-    type State = LanternGameState<'a, N_ROWS, N_COLS>;
+pub struct LanternGameState<const N_ROWS: usize, const N_COLS: usize> {
+    maze: Maze<N_ROWS, N_COLS>,
+    explorer: Explorer<N_ROWS, N_COLS>,
+    lantern: Lantern,
+    /// show a map of every room the player has seen next to the lantern's view
+    minimap: bool,
+    trail: TrailView,
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> StatefulWidget for LanternGame<N_ROWS, N_COLS> {
+    type State = LanternGameState<N_ROWS, N_COLS>;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let maze = &state.maze;
+        let explorer = &state.explorer;
+        let (lantern, trail) = (state.lantern, state.trail);
+        let max_visits = maze.max_visits();
         let c = Canvas::default()
             .x_bounds([ui::MIN_X, ui::MAX_X])
             .y_bounds([ui::MIN_Y, ui::MAX_Y])
             .background_color(ui::BG_COLOR)
             .paint(move |ctx| {
-                let curr_ix = state.maze.current_ix;
-                for ix in Ix2Neighbors::<N_ROWS, N_COLS>::new(state.maze.current_ix)
-                    .chain(std::iter::once(curr_ix))
-                {
-                    let x = -70.0 + ui::ROOM_SIZE * signed_diff(ix.x(), curr_ix.x());
-                    let y = 30.0 - ui::ROOM_SIZE * signed_diff(ix.y(), curr_ix.y());
+                for ((d_rows, d_cols), ix) in lantern.lit(maze) {
+                    let x = -28.0 + ui::ROOM_SIZE * d_cols as f64;
+                    let y = 30.0 - ui::ROOM_SIZE * d_rows as f64;
                     let label_x = x + (ui::SEG_LEN * 3.0);
                     let label_y = y - (ui::SEG_LEN * 4.0);
-                    if ix == state.maze.goal {
-                        ctx.print(label_x, label_y, "\u{1f945}")
+                    // the lantern shows what's lying around in rooms it lights up, even ones the
+                    // player hasn't been in yet
+                    if let Some(label) = ui::goal_label(&maze.goals, ix) {
+                        ctx.print(label_x, label_y, label)
+                    } else if let Some(item) = maze.rooms[ix].item {
+                        ctx.print(label_x, label_y, ui::item_label(item))
                     };
-                    if state.is_seen(&ix) {
-                        let room = &state.maze.rooms[ix];
-                        let view = RoomView { x, y, room };
+                    if explorer.is_seen(ix) {
+                        let view = RoomView {
+                            x,
+                            y,
+                            room: &maze.rooms[ix],
+                            wrap_doors: maze.wrap_doors(ix),
+                            fade: 0.0,
+                            marks: explorer.chalk.get(ix),
+                        };
                         ctx.draw(&view);
-                        if ix == state.maze.current_ix && ix == state.maze.goal {
+                        ctx.draw(&Breadcrumb {
+                            x,
+                            y,
+                            visits: maze.visits(ix),
+                            max_visits,
+                            view: trail,
+                        });
+                        if ix == maze.current_ix && maze.goals.contains(ix) {
                             ctx.print(label_x, label_y, "\u{1f940}")
-                        } else if ix == state.maze.current_ix {
+                        } else if ix == maze.current_ix {
                             ctx.print(label_x, label_y, "\u{1f600}")
                         }
-                        ctx.layer();
                     } else {
-                        let mut unseen: Vec<Direction> = Vec::with_capacity(4);
-                        if ix.north().map(|i| !state.is_seen(&i)).unwrap_or(true) {
-                            unseen.push(Direction::North);
-                        }
-                        if ix.south().map(|i| !state.is_seen(&i)).unwrap_or(true) {
-                            unseen.push(Direction::South);
-                        }
-                        if ix.east().map(|i| !state.is_seen(&i)).unwrap_or(true) {
-                            unseen.push(Direction::East);
-                        }
-                        if ix.west().map(|i| !state.is_seen(&i)).unwrap_or(true) {
-                            unseen.push(Direction::West);
-                        }
-                        ctx.draw(&UnseenRoomView {
-                            x,
-                            y,
-                            hidden_walls: unseen,
-                        });
-                        ctx.layer();
+                        let hidden_walls = DirectionsIter::new()
+                            .filter(|dir| {
+                                !maze.neighbor(ix, *dir).is_some_and(|i| explorer.is_seen(i))
+                            })
+                            .collect();
+                        ctx.draw(&UnseenRoomView { x, y, hidden_walls });
                     }
+                    ctx.layer();
                 }
             });
         Widget::render(c, area, buf);
     }
 }
 
-pub fn game<const N_ROWS: usize, const N_COLS: usize>(
-    terminal: &mut DefaultTerminal,
-    maze: &mut Maze<N_ROWS, N_COLS>,
-) -> Result<Outcome> {
-    let mut st: LanternGameState<N_ROWS, N_COLS> = LanternGameState {
-        maze,
-        seen: BTreeSet::new(),
-    };
-    loop {
-        st.insert_current_ix();
-        terminal.draw(|frame: &mut Frame| {
-            frame.render_stateful_widget(LanternGame::new(), frame.area(), &mut st)
-        })?;
-        if st.is_done() {
-            return Ok(Outcome::Win);
+impl<const N_ROWS: usize, const N_COLS: usize> GameMode for LanternGameState<N_ROWS, N_COLS> {
+    fn handle_event(&mut self, event: MazeEvent) {
+        match event {
+            MazeEvent::OtherKey(KeyCode::Char('m')) => self.minimap = !self.minimap,
+            // undoing burns fuel like any other move, but there's nothing to pick up going back
+            event => {
+                if self.explorer.handle_event(&mut self.maze, event) {
+                    self.lantern.burn(self.maze.last_pickup);
+                }
+            }
         }
-        match event::read()?.into() {
-            MazeEvent::MoveN => &st.move_north(),
-            MazeEvent::MoveS => &st.move_south(),
-            MazeEvent::MoveE => &st.move_east(),
-            MazeEvent::MoveW => &st.move_west(),
-            MazeEvent::Quit => return Ok(Outcome::Quit),
-            _ => &(),
-        };
     }
+    fn update(&mut self) {
+        self.explorer.look(&self.maze);
+    }
+    fn render(&mut self, frame: &mut Frame) {
+        let [maze_area, hud_area] = ui::hud_areas(frame.area());
+        if self.minimap {
+            let [lantern_area, map_area] =
+                Layout::horizontal([Constraint::Min(0), Constraint::Percentage(35)])
+                    .areas(maze_area);
+            frame.render_widget(
+                ui::ExploredMap {
+                    maze: &self.maze,
+                    fade: |ix| self.explorer.fade(ix),
+                    all_goals: false,
+                    trail: self.trail,
+                    chalk: &self.explorer.chalk,
+                },
+                map_area,
+            );
+            frame.render_stateful_widget(LanternGame::new(), lantern_area, self);
+        } else {
+            frame.render_stateful_widget(LanternGame::new(), maze_area, self);
+        }
+        let mut items = self.maze.status();
+        if let Some(fuel) = self.lantern.fuel {
+            items.push(format!("fuel {fuel}"));
+        }
+        items.extend(self.explorer.chalk.status());
+        frame.render_widget(ui::Hud { items }, hud_area);
+    }
+    fn outcome(&self) -> Option<Outcome> {
+        self.maze.is_done().then_some(Outcome::Win)
+    }
+    fn trail(&self) -> ([&'static str; 2], Vec<(usize, usize)>) {
+        grid_trail(&self.maze)
+    }
+    fn position(&self) -> (usize, usize) {
+        (self.maze.current_ix.y(), self.maze.current_ix.x())
+    }
+    fn save(&self, out: &mut Writer) {
+        self.maze.save(out);
+        self.explorer.save(out);
+        self.lantern.save(out);
+        out.field("minimap", self.minimap);
+        out.field("trail_view", self.trail.token());
+    }
+}
+
+pub fn new_game<const N_ROWS: usize, const N_COLS: usize>(
+    rng: &mut StdRng,
+    options: &Options,
+) -> Result<Box<dyn GameMode>> {
+    let mut maze = new_seeded::<N_ROWS, N_COLS>(rng, options)?;
+    if options.lantern.fuel.is_some() {
+        scatter_fuel(&mut maze, REFUEL_PICKUPS, rng);
+    }
+    Ok(Box::new(LanternGameState {
+        maze,
+        explorer: Explorer::new(None, options.chalk),
+        lantern: options.lantern,
+        minimap: options.minimap,
+        trail: options.trail,
+    }))
+}
+
+pub fn load<const N_ROWS: usize, const N_COLS: usize>(
+    input: &mut Reader,
+) -> Result<Box<dyn GameMode>> {
+    Ok(Box::new(LanternGameState {
+        maze: Maze::<N_ROWS, N_COLS>::load(input)?,
+        explorer: Explorer::load(input)?,
+        lantern: Lantern::load(input)?,
+        minimap: input.parse("minimap")?,
+        trail: input.token("trail_view")?,
+    }))
 }
 
-fn signed_diff(a: usize, b: usize) -> f64 {
-    let mut res = a.abs_diff(b) as f64;
-    if b > a {
-        res *= -1.0;
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_out_of_fuel() {
+        let maze = Maze::<7, 7>::new();
+        let mut lantern = Lantern {
+            radius: 2,
+            shape: LightShape::Square,
+            fuel: Some(11),
+        };
+        assert_eq!(2, lantern.radius());
+        lantern.burn(None);
+        assert_eq!(1, lantern.radius());
+        for _ in 0..10 {
+            lantern.burn(None);
+        }
+        assert_eq!(vec![((0, 0), maze.current_ix)], lantern.lit(&maze));
+        lantern.burn(Some(Item::Fuel));
+        assert_eq!(2, lantern.radius());
     }
-    res
 }
@@ -1,4 +1,8 @@
-use super::{Game, Outcome};
+use super::{
+    Outcome,
+    options::{Options, Setting},
+};
+use rand::Rng;
 use ratatui::{
     buffer::Buffer,
     layout::{Alignment, Constraint, Layout, Rect},
@@ -9,46 +13,108 @@ use ratatui::{
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum MenuChoice {
     Quit,
-    Game(Game),
-}
-
-impl MenuChoice {
-    fn to_list<'a>() -> List<'a> {
-        List::new(["Basic", "Hidden", "Lantern", "Quit"])
-    }
+    /// carry on with the saved game
+    Continue,
+    /// one of the registered game modes, by position
+    Game(usize),
+    Toggle(Setting),
 }
 
-impl From<usize> for MenuChoice {
-    fn from(val: usize) -> Self {
-        match val {
-            0 => MenuChoice::Game(Game::Basic),
-            1 => MenuChoice::Game(Game::Hidden),
-            2 => MenuChoice::Game(Game::Lantern),
-            _ => MenuChoice::Quit,
-        }
-    }
-}
+/// settings listed in the menu, after the games
+const SETTINGS: [Setting; 20] = [
+    Setting::Wrap,
+    Setting::Shape,
+    Setting::MazeFile,
+    Setting::Weave,
+    Setting::Checkpoints,
+    Setting::Win,
+    Setting::Placement,
+    Setting::Items,
+    Setting::ItemsRequired,
+    Setting::Undos,
+    Setting::UndoCost,
+    Setting::Chalk,
+    Setting::Memory,
+    Setting::LightRadius,
+    Setting::LightShape,
+    Setting::Fuel,
+    Setting::Minimap,
+    Setting::Trail,
+    Setting::SaveTrail,
+    Setting::Seed,
+];
 
 pub struct GameMenu;
 
 #[derive(Debug)]
 pub struct MenuState {
     list: ListState,
+    /// names of the games on offer
+    modes: Vec<&'static str>,
+    /// everything in the menu, top to bottom
+    choices: Vec<MenuChoice>,
     pub choice: Option<MenuChoice>,
+    pub options: Options,
     prev_outcome: Option<Outcome>,
+    error: Option<String>,
+    /// anything else to say about the last game, like where its trail was saved
+    note: Option<String>,
+    /// what there is to say about the saved game, if there is one
+    saved: Option<String>,
 }
 
 impl MenuState {
     pub fn game_over(&mut self, outcome: Outcome) {
         self.choice = None;
         self.prev_outcome = Some(outcome);
+        self.error = None;
+        self.note = None;
+        self.list.select_first();
+    }
+    /// go back to the menu without starting a game, e.g. because a shape file is broken
+    pub fn game_failed(&mut self, error: String) {
+        self.choice = None;
+        self.error = Some(error);
+    }
+    pub fn note(&mut self, note: Option<String>) {
+        self.note = note;
+    }
+    /// offer to carry on with a saved game at the top of the menu, or stop offering it
+    pub fn saved(&mut self, summary: Option<String>) {
+        let offered = self.saved.is_some();
+        match (offered, summary.is_some()) {
+            (false, true) => self.choices.insert(0, MenuChoice::Continue),
+            (true, false) => {
+                self.choices.remove(0);
+            }
+            _ => (),
+        }
+        self.saved = summary;
         self.list.select_first();
     }
     pub fn unchoose(&mut self) {
         self.choice = None;
     }
+    pub fn toggle(&mut self, setting: Setting) {
+        self.options.toggle(setting);
+        self.choice = None;
+    }
     pub fn choose(&mut self) {
-        self.choice = self.list.selected().map(MenuChoice::from)
+        self.choice = self
+            .list
+            .selected()
+            .map(|n| self.choices.get(n).copied().unwrap_or(MenuChoice::Quit))
+    }
+    fn label(&self, choice: MenuChoice) -> String {
+        match choice {
+            MenuChoice::Quit => "Quit".into(),
+            MenuChoice::Continue => match &self.saved {
+                Some(summary) => format!("Continue ({summary})"),
+                None => "Continue".into(),
+            },
+            MenuChoice::Game(n) => self.modes[n].into(),
+            MenuChoice::Toggle(setting) => self.options.label(setting),
+        }
     }
     pub fn select_previous(&mut self) {
         self.list.select_previous();
@@ -59,11 +125,26 @@ impl MenuState {
     pub fn select_quit(&mut self) {
         self.list.select_last();
     }
-    pub fn outcome_msg(&self) -> &str {
+    /// seed for the next game: the one picked in the menu, or a new one from `rng`
+    pub fn next_seed<R: Rng + ?Sized>(&mut self, rng: &mut R) -> u64 {
+        let seed = self.options.seed.unwrap_or_else(|| rng.random());
+        self.options.last_seed = Some(seed);
+        seed
+    }
+    /// a new seed from `rng` even if one was picked in the menu, for when the player asks for
+    /// a new maze
+    pub fn new_seed<R: Rng + ?Sized>(&mut self, rng: &mut R) -> u64 {
+        let seed = rng.random();
+        self.options.last_seed = Some(seed);
+        seed
+    }
+    pub fn outcome_msg(&self) -> String {
+        if let Some(error) = &self.error {
+            return error.clone();
+        }
         match self.prev_outcome {
-            None => "",
-            Some(Outcome::Win) => "you won!",
-            Some(Outcome::Quit) => "you quit",
+            None => String::new(),
+            Some(outcome) => outcome_msg(outcome, self.options.last_seed, self.note.as_deref()),
         }
     }
     fn list_state_mut(&mut self) -> &mut ListState {
@@ -71,12 +152,41 @@ impl MenuState {
     }
 }
 
-impl Default for MenuState {
-    fn default() -> Self {
+/// how a game went, which maze it was and anything else worth saying about it
+pub fn outcome_msg(outcome: Outcome, seed: Option<u64>, note: Option<&str>) -> String {
+    let mut msg = match outcome {
+        Outcome::Win => "you won!",
+        Outcome::Quit | Outcome::Restart | Outcome::NewMaze => "you quit",
+        Outcome::Save => "game saved",
+    }
+    .to_string();
+    if let Some(seed) = seed {
+        msg.push_str(&format!("\n(seed {seed})"));
+    }
+    if let Some(note) = note {
+        msg.push('\n');
+        msg.push_str(note);
+    }
+    msg
+}
+
+impl MenuState {
+    pub fn new(modes: Vec<&'static str>) -> Self {
+        let choices = (0..modes.len())
+            .map(MenuChoice::Game)
+            .chain(SETTINGS.map(MenuChoice::Toggle))
+            .chain([MenuChoice::Quit])
+            .collect();
         let mut this = MenuState {
             list: ListState::default(),
+            modes,
+            choices,
             choice: None,
+            options: Options::default(),
             prev_outcome: None,
+            error: None,
+            note: None,
+            saved: None,
         };
         this.list.select_first();
         this
@@ -95,7 +205,7 @@ impl StatefulWidget for GameMenu {
         let inner_area = b.inner(area);
         let vertical = Layout::vertical([Constraint::Min(0), Constraint::Length(5)]);
         let [menu_area, outcome_area] = vertical.areas(inner_area);
-        let l = MenuChoice::to_list()
+        let l = List::new(state.choices.iter().map(|choice| state.label(*choice)))
             .block(Block::bordered())
             .fg(Color::Green)
             .highlight_style(Style::new().reversed())
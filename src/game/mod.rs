@@ -1,35 +1,82 @@
-use crate::{maze::Maze, movement::MazeEvent};
-use color_eyre::Result;
-use crossterm::event;
-use rand::rngs::ThreadRng;
-use ratatui::Frame;
+use crate::{
+    events::{EventSource, TerminalEvents},
+    format::text,
+    mask::Mask,
+    maze::Maze,
+    movement::MazeEvent,
+    save::{self, Reader, SavedGame, Writer},
+};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+use rand::{
+    SeedableRng,
+    rngs::{StdRng, ThreadRng},
+};
+use ratatui::{Frame, Terminal, backend::Backend};
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
 
 pub mod basic;
+pub mod explore;
 pub mod hidden;
 pub mod lantern;
 pub mod menu;
+pub mod mode;
+pub mod options;
+pub mod placement;
+pub mod polar;
+pub mod post_game;
 pub mod seeders;
+pub mod trail;
 
 use menu::{MenuChoice, MenuState};
-pub use seeders::{seed_doors_naive, seed_doors_path};
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum Game {
-    Basic,
-    Hidden,
-    Lantern,
-}
+use mode::{GameMode, ModeEntry};
+use options::Options;
+use post_game::AfterGame;
+pub use seeders::{
+    place_checkpoints, scatter_fuel, scatter_items, seed_doors_backtrack, seed_doors_naive,
+    seed_doors_path, seed_doors_weave, seed_polar_doors,
+};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Outcome {
     Win,
     Quit,
+    /// the player wants to start the same maze over
+    Restart,
+    /// the player wants a different maze
+    NewMaze,
+    /// the player wants to stop and carry on later
+    Save,
 }
 
 pub fn game_loop<const N_ROWS: usize, const N_COLS: usize>() -> Result<()> {
     let mut terminal = ratatui::init();
+    let result = menu_loop::<N_ROWS, N_COLS, _>(
+        &mut terminal,
+        &mut TerminalEvents,
+        Path::new(save::SAVE_PATH),
+    );
+    ratatui::restore();
+    result
+}
+
+/// the menu, and whatever gets played from it, until the player quits or `events` runs out.
+/// games are saved to `save_path` when the player asks, and picked up from there again
+pub fn menu_loop<const N_ROWS: usize, const N_COLS: usize, B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut dyn EventSource,
+    save_path: &Path,
+) -> Result<()> {
     let mut rng = ThreadRng::default();
-    let mut menu_state = MenuState::default();
+    let modes = modes::<N_ROWS, N_COLS>();
+    let mut menu_state = MenuState::new(modes.iter().map(|mode| mode.name).collect());
+    menu_state.saved(saved_summary(save_path));
     loop {
         terminal.draw(|frame: &mut Frame| {
             frame.render_stateful_widget(menu::GameMenu, frame.area(), &mut menu_state)
@@ -37,27 +84,49 @@ pub fn game_loop<const N_ROWS: usize, const N_COLS: usize>() -> Result<()> {
         match menu_state.choice {
             None => (),
             Some(MenuChoice::Quit) => break,
-            Some(MenuChoice::Game(Game::Basic)) => {
-                let mut maze = new_seeded::<N_ROWS, N_COLS>(&mut rng);
-                let outcome = basic::game(&mut terminal, &mut maze)?;
-                menu_state.game_over(outcome);
+            Some(MenuChoice::Continue) => {
+                match resume(&modes, save_path) {
+                    Ok((mode, session)) => play(
+                        terminal,
+                        events,
+                        &mut menu_state,
+                        &mut rng,
+                        mode,
+                        session,
+                        save_path,
+                    )?,
+                    Err(e) => menu_state.game_failed(format!("{e:#}")),
+                }
+                menu_state.saved(saved_summary(save_path));
                 continue;
             }
-            Some(MenuChoice::Game(Game::Hidden)) => {
-                let mut maze = new_seeded::<N_ROWS, N_COLS>(&mut rng);
-                let outcome = hidden::game(&mut terminal, &mut maze)?;
-                menu_state.game_over(outcome);
+            Some(MenuChoice::Game(n)) => {
+                let seed = menu_state.next_seed(&mut rng);
+                match Session::new(modes[n], seed, &menu_state.options) {
+                    Ok(session) => play(
+                        terminal,
+                        events,
+                        &mut menu_state,
+                        &mut rng,
+                        modes[n],
+                        session,
+                        save_path,
+                    )?,
+                    Err(e) => menu_state.game_failed(e.to_string()),
+                }
+                menu_state.saved(saved_summary(save_path));
                 continue;
             }
-            Some(MenuChoice::Game(Game::Lantern)) => {
-                let mut maze = new_seeded::<N_ROWS, N_COLS>(&mut rng);
-                let outcome = lantern::game(&mut terminal, &mut maze)?;
-                menu_state.game_over(outcome);
+            Some(MenuChoice::Toggle(setting)) => {
+                menu_state.toggle(setting);
                 continue;
             }
         };
         menu_state.unchoose();
-        match event::read()?.into() {
+        let Some(event) = events.next_event()? else {
+            break;
+        };
+        match event.into() {
             MazeEvent::MoveN => &menu_state.select_previous(),
             MazeEvent::MoveS => &menu_state.select_next(),
             MazeEvent::Quit => &menu_state.select_quit(),
@@ -65,14 +134,336 @@ pub fn game_loop<const N_ROWS: usize, const N_COLS: usize>() -> Result<()> {
             _ => &(),
         };
     }
-    ratatui::restore();
     Ok(())
 }
 
-fn new_seeded<const N_ROWS: usize, const N_COLS: usize>(
+/// every game the menu offers, in the order it offers them; a new mode only needs adding here
+pub fn modes<const N_ROWS: usize, const N_COLS: usize>() -> Vec<ModeEntry> {
+    vec![
+        ModeEntry {
+            name: "Basic",
+            new_game: basic::new_game::<N_ROWS, N_COLS>,
+            load: basic::load::<N_ROWS, N_COLS>,
+        },
+        ModeEntry {
+            name: "Hidden",
+            new_game: hidden::new_game::<N_ROWS, N_COLS>,
+            load: hidden::load::<N_ROWS, N_COLS>,
+        },
+        ModeEntry {
+            name: "Lantern",
+            new_game: lantern::new_game::<N_ROWS, N_COLS>,
+            load: lantern::load::<N_ROWS, N_COLS>,
+        },
+        ModeEntry {
+            name: "Polar",
+            new_game: polar::new_game::<N_ROWS, N_COLS>,
+            load: polar::load,
+        },
+    ]
+}
+
+/// a game being played, and what's needed to start it again or save it
+struct Session {
+    seed: u64,
+    /// time spent playing it so far
+    elapsed: Duration,
+    /// the game as it was before the first move
+    start: String,
+    game: Box<dyn GameMode>,
+}
+
+impl Session {
+    fn new(mode: ModeEntry, seed: u64, options: &Options) -> Result<Self> {
+        let game = (mode.new_game)(&mut StdRng::seed_from_u64(seed), options)?;
+        Ok(Self {
+            seed,
+            elapsed: Duration::ZERO,
+            start: save_game(game.as_ref()),
+            game,
+        })
+    }
+    /// put the game back how it started
+    fn restart(&mut self, mode: ModeEntry) -> Result<()> {
+        self.game = (mode.load)(&mut Reader::new(&self.start))?;
+        self.elapsed = Duration::ZERO;
+        Ok(())
+    }
+    fn save(&self, mode: ModeEntry) -> SavedGame {
+        SavedGame {
+            mode: mode.name.into(),
+            seed: self.seed,
+            elapsed: self.elapsed,
+            moves: self.game.trail().1.len().saturating_sub(1),
+            start: self.start.clone(),
+            current: save_game(self.game.as_ref()),
+        }
+    }
+}
+
+fn save_game(game: &dyn GameMode) -> String {
+    let mut out = Writer::default();
+    game.save(&mut out);
+    out.finish()
+}
+
+/// load the game saved at `save_path`, then delete the save now it's being played
+fn resume(modes: &[ModeEntry], save_path: &Path) -> Result<(ModeEntry, Session)> {
+    let Some(saved) = SavedGame::read(save_path)? else {
+        bail!("nothing's been saved");
+    };
+    let Some(mode) = modes.iter().find(|mode| mode.name == saved.mode) else {
+        bail!("there's no {} game any more", saved.mode);
+    };
+    let game = (mode.load)(&mut Reader::new(&saved.current))
+        .wrap_err_with(|| format!("couldn't load {}", save_path.display()))?;
+    fs::remove_file(save_path)
+        .wrap_err_with(|| format!("couldn't remove {}", save_path.display()))?;
+    let session = Session {
+        seed: saved.seed,
+        elapsed: saved.elapsed,
+        start: saved.start,
+        game,
+    };
+    Ok((*mode, session))
+}
+
+/// what the menu says about the game saved at `save_path`, if there's one that can be read
+fn saved_summary(save_path: &Path) -> Option<String> {
+    SavedGame::read(save_path)
+        .ok()
+        .flatten()
+        .map(|s| s.summary())
+}
+
+/// keep playing `mode` until the player goes back to the menu: restarting puts the same game
+/// back how it started, asking for a new maze makes one from a fresh seed, and saving writes
+/// the game to `save_path` to carry on later
+fn play<B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut dyn EventSource,
+    menu_state: &mut MenuState,
     rng: &mut ThreadRng,
-) -> Maze<N_ROWS, N_COLS> {
-    let mut maze = Maze::<N_ROWS, N_COLS>::default();
-    seed_doors_path(&mut maze, rng);
-    maze
+    mode: ModeEntry,
+    mut session: Session,
+    save_path: &Path,
+) -> Result<()> {
+    loop {
+        let began = Instant::now();
+        let outcome = mode::run(terminal, session.game.as_mut(), events)?;
+        session.elapsed += began.elapsed();
+        if outcome == Outcome::Save {
+            let note = match session.save(mode).write(save_path) {
+                Ok(path) => format!("saved to {}", path.display()),
+                Err(e) => format!("{e:#}"),
+            };
+            menu_state.game_over(outcome);
+            menu_state.note(Some(note));
+            return Ok(());
+        }
+        let (columns, rooms) = session.game.trail();
+        let note = save_trail(&menu_state.options, columns, rooms);
+        let after = match outcome {
+            Outcome::Restart => AfterGame::Retry,
+            Outcome::NewMaze => AfterGame::New,
+            Outcome::Quit | Outcome::Save => AfterGame::Menu,
+            Outcome::Win => {
+                let msg = menu::outcome_msg(outcome, Some(session.seed), note.as_deref());
+                post_game::choose(terminal, events, &msg)?
+            }
+        };
+        let next = match after {
+            AfterGame::Retry => session.restart(mode),
+            AfterGame::New => {
+                let seed = menu_state.new_seed(rng);
+                Session::new(mode, seed, &menu_state.options).map(|new| session = new)
+            }
+            AfterGame::Menu => {
+                menu_state.game_over(outcome);
+                menu_state.note(note);
+                return Ok(());
+            }
+        };
+        if let Err(e) = next {
+            menu_state.game_failed(e.to_string());
+            return Ok(());
+        }
+    }
+}
+
+/// save the last game's trail if the menu says to, and say where it went
+fn save_trail(
+    options: &Options,
+    columns: [&str; 2],
+    rooms: impl IntoIterator<Item = (usize, usize)>,
+) -> Option<String> {
+    if !options.save_trail {
+        return None;
+    }
+    let name = match options.last_seed {
+        Some(seed) => seed.to_string(),
+        None => "trail".into(),
+    };
+    match trail::save(&name, columns, rooms) {
+        Ok(path) => Some(format!("trail saved to {}", path.display())),
+        Err(e) => Some(format!("{e:#}")),
+    }
+}
+
+fn grid_trail<const N_ROWS: usize, const N_COLS: usize>(
+    maze: &Maze<N_ROWS, N_COLS>,
+) -> ([&'static str; 2], Vec<(usize, usize)>) {
+    (
+        ["row", "col"],
+        maze.trail.iter().map(|ix| (ix.y(), ix.x())).collect(),
+    )
+}
+
+/// the maze every grid game makes from `rng` and the menu's options
+pub fn new_seeded<const N_ROWS: usize, const N_COLS: usize>(
+    rng: &mut StdRng,
+    options: &Options,
+) -> Result<Maze<N_ROWS, N_COLS>> {
+    let mut maze = match &options.maze_file {
+        // a hand-made maze comes with its own doors, goals and items
+        Some(name) => text::load(name)?,
+        None => {
+            let mask = match &options.shape {
+                Some(name) => Mask::load(name)?,
+                None => Mask::full(),
+            };
+            let mut maze = Maze::<N_ROWS, N_COLS>::with_mask(mask, options.wrap);
+            options.placement.place_before_carving(&mut maze, rng);
+            if options.weave {
+                seed_doors_weave(&mut maze, rng);
+            } else {
+                seed_doors_path(&mut maze, rng);
+            }
+            options.placement.place_after_carving(&mut maze, rng);
+            place_checkpoints(&mut maze, options.checkpoints, rng);
+            scatter_items(&mut maze, options.items, rng);
+            maze
+        }
+    };
+    maze.goals.condition = options.win;
+    // only what the player can get to counts towards what they have to pick up
+    let dists = maze.distances_from(maze.current_ix);
+    let reachable = maze
+        .indices()
+        .filter(|ix| maze.rooms[*ix].item.is_some() && dists[*ix].is_some())
+        .count();
+    maze.items_required = options.items_required.min(reachable);
+    maze.undos_left = options.undos;
+    maze.undo_cost = options.undo_cost;
+    Ok(maze)
+}
+
+/// options with everything a maze can be made with turned on, for tests that save, load or
+/// export one
+#[cfg(test)]
+pub(crate) fn test_options() -> Options {
+    Options {
+        weave: true,
+        wrap: true,
+        checkpoints: 2,
+        items: 10,
+        ..Options::default()
+    }
+}
+
+/// the 7x7 maze made from `seed` with [`test_options`]
+#[cfg(test)]
+pub(crate) fn test_maze(seed: u64) -> Maze<7, 7> {
+    new_seeded(&mut StdRng::seed_from_u64(seed), &test_options()).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::maze::Item;
+    use placement::Placement;
+
+    #[test]
+    fn test_same_seed_same_maze() {
+        let options = Options {
+            weave: true,
+            checkpoints: 2,
+            placement: Placement::FarthestPair,
+            ..Options::default()
+        };
+        let a = new_seeded::<7, 7>(&mut StdRng::seed_from_u64(7), &options).unwrap();
+        let b = new_seeded::<7, 7>(&mut StdRng::seed_from_u64(7), &options).unwrap();
+        assert_eq!(format!("{:?}", a.rooms), format!("{:?}", b.rooms));
+        assert_eq!(a.current_ix, b.current_ix);
+        assert_eq!(a.goals, b.goals);
+    }
+
+    #[test]
+    fn test_items_reachable() {
+        let options = Options {
+            items: 15,
+            items_required: 10,
+            ..Options::default()
+        };
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut maze = new_seeded::<7, 7>(&mut rng, &options).unwrap();
+            // what a lantern game with limited fuel adds
+            scatter_fuel(&mut maze, lantern::REFUEL_PICKUPS, &mut rng);
+            let dists = maze.distances_from(maze.current_ix);
+            let items: Vec<_> = maze
+                .indices()
+                .filter(|ix| maze.rooms[*ix].item.is_some())
+                .collect();
+            assert!(items.iter().all(|ix| dists[*ix].is_some()), "seed {seed}");
+            let fuel = items
+                .iter()
+                .filter(|ix| maze.rooms[**ix].item == Some(Item::Fuel))
+                .count();
+            assert!(fuel > 0, "seed {seed}");
+            assert!(maze.items_required <= items.len() - fuel, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let options = Options {
+            undos: Some(3),
+            chalk: Some(5),
+            memory: Some(10),
+            lantern: lantern::Lantern {
+                fuel: Some(30),
+                ..Default::default()
+            },
+            ..test_options()
+        };
+        let events = || {
+            [
+                MazeEvent::MoveE,
+                MazeEvent::MoveS,
+                MazeEvent::Mark,
+                MazeEvent::MarkDoor(crate::Direction::North),
+                MazeEvent::MoveE,
+                MazeEvent::Undo,
+                MazeEvent::MoveN,
+            ]
+        };
+        for mode in modes::<7, 7>() {
+            let mut session = Session::new(mode, 3, &options).unwrap();
+            for event in events() {
+                session.game.handle_event(event);
+                session.game.update();
+            }
+            let saved = save_game(session.game.as_ref());
+            let loaded = (mode.load)(&mut Reader::new(&saved)).unwrap();
+            assert_eq!(saved, save_game(loaded.as_ref()), "{}", mode.name);
+            assert_eq!(session.game.position(), loaded.position());
+            session.restart(mode).unwrap();
+            let fresh = Session::new(mode, 3, &options).unwrap();
+            assert_eq!(
+                save_game(fresh.game.as_ref()),
+                save_game(session.game.as_ref())
+            );
+        }
+    }
 }
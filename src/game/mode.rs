@@ -0,0 +1,64 @@
+use super::{Outcome, options::Options};
+use crate::{
+    events::EventSource,
+    movement::MazeEvent,
+    save::{Reader, Writer},
+};
+use color_eyre::Result;
+use rand::rngs::StdRng;
+use ratatui::{Frame, Terminal, backend::Backend};
+
+/// one way of playing a maze; the driver in [`run`] does the rest
+pub trait GameMode {
+    /// react to a key press; quitting, restarting and asking for a new maze are handled before
+    /// it gets here
+    fn handle_event(&mut self, event: MazeEvent);
+    /// anything that happens after each move, before the next draw
+    fn update(&mut self) {}
+    fn render(&mut self, frame: &mut Frame);
+    /// how the game ended, once it has
+    fn outcome(&self) -> Option<Outcome>;
+    /// names of the two numbers that pick out a room, and the rooms the player went through, so
+    /// the trail can be saved
+    fn trail(&self) -> ([&'static str; 2], Vec<(usize, usize)>);
+    /// the room the player's in, as the same two numbers the trail uses
+    fn position(&self) -> (usize, usize);
+    /// write out everything needed to carry on later; the mode's `load` reads it back
+    fn save(&self, out: &mut Writer);
+}
+
+/// a game the menu offers
+#[derive(Copy, Clone)]
+pub struct ModeEntry {
+    pub name: &'static str,
+    /// a new game on a maze made from `rng`; the same seed always gives the same game
+    pub new_game: fn(&mut StdRng, &Options) -> Result<Box<dyn GameMode>>,
+    /// a game written by [`GameMode::save`]
+    pub load: fn(&mut Reader) -> Result<Box<dyn GameMode>>,
+}
+
+/// draw, check for the end of the game, read a key, repeat. running out of keys counts as
+/// quitting
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    mode: &mut dyn GameMode,
+    events: &mut dyn EventSource,
+) -> Result<Outcome> {
+    loop {
+        mode.update();
+        terminal.draw(|frame: &mut Frame| mode.render(frame))?;
+        if let Some(outcome) = mode.outcome() {
+            return Ok(outcome);
+        }
+        let Some(event) = events.next_event()? else {
+            return Ok(Outcome::Quit);
+        };
+        match event.into() {
+            MazeEvent::Quit => return Ok(Outcome::Quit),
+            MazeEvent::Restart => return Ok(Outcome::Restart),
+            MazeEvent::NewMaze => return Ok(Outcome::NewMaze),
+            MazeEvent::Save => return Ok(Outcome::Save),
+            event => mode.handle_event(event),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use super::{
+    lantern::{self, Lantern, LightShape},
+    placement::Placement,
+};
+use crate::{format::text, goals::WinCondition, mask, ui::TrailView};
+
+/// most checkpoints the menu lets you ask for
+pub const MAX_CHECKPOINTS: usize = 3;
+/// choices for how many items to scatter around the maze
+pub const ITEM_COUNTS: [usize; 4] = [0, 5, 10, 15];
+/// choices for how many items have to be picked up before a goal counts
+pub const ITEMS_REQUIRED: [usize; 4] = [0, 3, 5, 10];
+/// choices for how many moves can be undone each game; `None` is as many as you like
+pub const UNDO_CHOICES: [Option<usize>; 4] = [None, Some(10), Some(3), Some(0)];
+/// choices for how much score each undo costs
+pub const UNDO_COSTS: [usize; 3] = [0, 1, 5];
+/// choices for how many chalk marks the player gets each game; `None` never runs out
+pub const CHALK_CHOICES: [Option<usize>; 5] = [None, Some(20), Some(10), Some(5), Some(0)];
+/// choices for how many moves it takes to forget a room in hidden mode; `None` never forgets
+pub const MEMORY_CHOICES: [Option<usize>; 4] = [None, Some(10), Some(20), Some(40)];
+
+/// settings chosen in the menu that apply to every game
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Options {
+    /// edges of the maze wrap around to the opposite edge
+    pub wrap: bool,
+    /// name of a shape from the shapes directory to cut the maze out of
+    pub shape: Option<String>,
+    /// name of a hand-made maze from the mazes directory to play instead of a generated one
+    pub maze_file: Option<String>,
+    /// corridors can pass over and under each other
+    pub weave: bool,
+    /// extra goals placed along the way to the last one
+    pub checkpoints: usize,
+    /// which goals have to be reached to win
+    pub win: WinCondition,
+    /// where the start and goal go
+    pub placement: Placement,
+    /// number of items scattered around the maze
+    pub items: usize,
+    /// number of items that have to be picked up before a goal counts
+    pub items_required: usize,
+    /// how to show where the player has been
+    pub trail: TrailView,
+    /// save the player's trail to a file when a game ends
+    pub save_trail: bool,
+    /// seed to generate every maze from, instead of a new one each game
+    pub seed: Option<u64>,
+    /// moves that can be undone each game, if there's a limit
+    pub undos: Option<usize>,
+    /// score lost for each undo
+    pub undo_cost: usize,
+    /// chalk marks the player gets each game in hidden and lantern mode, if there's a limit
+    pub chalk: Option<usize>,
+    /// moves it takes to forget a room in hidden mode, if it's ever forgotten
+    pub memory: Option<usize>,
+    /// how far the lantern reaches and how long it lasts
+    pub lantern: Lantern,
+    /// start lantern games with the minimap showing
+    pub minimap: bool,
+    /// seed of the last maze played, which the seed setting can hold on to
+    pub last_seed: Option<u64>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Setting {
+    Wrap,
+    Shape,
+    MazeFile,
+    Weave,
+    Checkpoints,
+    Win,
+    Placement,
+    Items,
+    ItemsRequired,
+    Undos,
+    UndoCost,
+    Chalk,
+    Memory,
+    LightRadius,
+    LightShape,
+    Fuel,
+    Minimap,
+    Trail,
+    SaveTrail,
+    Seed,
+}
+
+impl Options {
+    pub fn toggle(&mut self, setting: Setting) {
+        match setting {
+            Setting::Wrap => self.wrap = !self.wrap,
+            Setting::Shape => self.shape = next_name(mask::available(), self.shape.as_deref()),
+            Setting::MazeFile => {
+                self.maze_file = next_name(text::available(), self.maze_file.as_deref())
+            }
+            Setting::Weave => self.weave = !self.weave,
+            Setting::Checkpoints => {
+                self.checkpoints = (self.checkpoints + 1) % (MAX_CHECKPOINTS + 1)
+            }
+            Setting::Win => {
+                self.win = match self.win {
+                    WinCondition::Any => WinCondition::All,
+                    WinCondition::All => WinCondition::Ordered,
+                    WinCondition::Ordered => WinCondition::Any,
+                }
+            }
+            Setting::Placement => {
+                let n = Placement::ALL.iter().position(|p| *p == self.placement);
+                self.placement = Placement::ALL[n.map_or(0, |n| (n + 1) % Placement::ALL.len())];
+            }
+            Setting::Items => self.items = next_of(&ITEM_COUNTS, self.items),
+            Setting::ItemsRequired => {
+                self.items_required = next_of(&ITEMS_REQUIRED, self.items_required)
+            }
+            Setting::Undos => {
+                let n = UNDO_CHOICES.iter().position(|u| *u == self.undos);
+                self.undos = UNDO_CHOICES[n.map_or(0, |n| (n + 1) % UNDO_CHOICES.len())];
+            }
+            Setting::UndoCost => self.undo_cost = next_of(&UNDO_COSTS, self.undo_cost),
+            Setting::Chalk => {
+                let n = CHALK_CHOICES.iter().position(|c| *c == self.chalk);
+                self.chalk = CHALK_CHOICES[n.map_or(0, |n| (n + 1) % CHALK_CHOICES.len())];
+            }
+            Setting::Memory => {
+                let n = MEMORY_CHOICES.iter().position(|m| *m == self.memory);
+                self.memory = MEMORY_CHOICES[n.map_or(0, |n| (n + 1) % MEMORY_CHOICES.len())];
+            }
+            Setting::LightRadius => {
+                self.lantern.radius = (self.lantern.radius + 1) % (lantern::MAX_RADIUS + 1)
+            }
+            Setting::LightShape => {
+                self.lantern.shape = match self.lantern.shape {
+                    LightShape::Square => LightShape::Manhattan,
+                    LightShape::Manhattan => LightShape::Doors,
+                    LightShape::Doors => LightShape::LineOfSight,
+                    LightShape::LineOfSight => LightShape::Square,
+                }
+            }
+            Setting::Fuel => {
+                let n = lantern::FUEL_CHOICES
+                    .iter()
+                    .position(|f| *f == self.lantern.fuel);
+                self.lantern.fuel =
+                    lantern::FUEL_CHOICES[n.map_or(0, |n| (n + 1) % lantern::FUEL_CHOICES.len())];
+            }
+            Setting::Minimap => self.minimap = !self.minimap,
+            Setting::Trail => {
+                self.trail = match self.trail {
+                    TrailView::Off => TrailView::Breadcrumbs,
+                    TrailView::Breadcrumbs => TrailView::Heatmap,
+                    TrailView::Heatmap => TrailView::Off,
+                }
+            }
+            Setting::SaveTrail => self.save_trail = !self.save_trail,
+            Setting::Seed => {
+                self.seed = match self.seed {
+                    Some(_) => None,
+                    None => self.last_seed,
+                }
+            }
+        }
+    }
+    pub fn label(&self, setting: Setting) -> String {
+        match setting {
+            Setting::Wrap => format!("Wrap edges: {}", on_off(self.wrap)),
+            Setting::Shape => format!("Shape: {}", self.shape.as_deref().unwrap_or("none")),
+            Setting::MazeFile => {
+                format!("Maze file: {}", self.maze_file.as_deref().unwrap_or("none"))
+            }
+            Setting::Weave => format!("Weave: {}", on_off(self.weave)),
+            Setting::Checkpoints => format!("Checkpoints: {}", self.checkpoints),
+            Setting::Win => format!(
+                "Win by reaching: {}",
+                match self.win {
+                    WinCondition::Any => "any goal",
+                    WinCondition::All => "every goal",
+                    WinCondition::Ordered => "every goal in order",
+                }
+            ),
+            Setting::Placement => format!("Start & goal: {}", self.placement.name()),
+            Setting::Items => format!("Items: {}", self.items),
+            Setting::ItemsRequired => match self.items_required {
+                0 => "Collect before goal: nothing".into(),
+                n => format!("Collect before goal: {n} items"),
+            },
+            Setting::Undos => match self.undos {
+                None => "Undo: unlimited".into(),
+                Some(0) => "Undo: off".into(),
+                Some(undos) => format!("Undo: {undos} per game"),
+            },
+            Setting::UndoCost => format!("Undo costs: {} points", self.undo_cost),
+            Setting::Chalk => match self.chalk {
+                None => "Chalk marks: unlimited".into(),
+                Some(marks) => format!("Chalk marks: {marks} per game"),
+            },
+            Setting::Memory => match self.memory {
+                None => "Hidden memory: forever".into(),
+                Some(moves) => format!("Hidden memory: {moves} moves"),
+            },
+            Setting::LightRadius => format!("Lantern radius: {}", self.lantern.radius),
+            Setting::LightShape => format!("Lantern light: {}", self.lantern.shape.name()),
+            Setting::Fuel => match self.lantern.fuel {
+                None => "Lantern fuel: unlimited".into(),
+                Some(fuel) => format!("Lantern fuel: {fuel} moves"),
+            },
+            Setting::Minimap => format!("Lantern minimap: {}", on_off(self.minimap)),
+            Setting::Trail => format!(
+                "Trail: {}",
+                match self.trail {
+                    TrailView::Off => "off",
+                    TrailView::Breadcrumbs => "breadcrumbs",
+                    TrailView::Heatmap => "heatmap",
+                }
+            ),
+            Setting::SaveTrail => {
+                format!("Save trail after each game: {}", on_off(self.save_trail))
+            }
+            Setting::Seed => match (self.seed, self.last_seed) {
+                (Some(seed), _) => format!("Seed: {seed}"),
+                (None, Some(_)) => "Seed: new each game (pick to replay the last one)".into(),
+                (None, None) => "Seed: new each game".into(),
+            },
+        }
+    }
+}
+
+/// the name after `curr` in `names`, going back to none after the last one
+fn next_name(names: Vec<String>, curr: Option<&str>) -> Option<String> {
+    let next_ix = match curr {
+        None => 0,
+        Some(name) => names.iter().position(|s| s == name).map_or(0, |i| i + 1),
+    };
+    names.get(next_ix).cloned()
+}
+
+/// the choice after `curr` in `choices`, going back to the first after the last one
+fn next_of(choices: &[usize], curr: usize) -> usize {
+    let n = choices.iter().position(|c| *c == curr);
+    choices[n.map_or(0, |n| (n + 1) % choices.len())]
+}
+
+fn on_off(val: bool) -> &'static str {
+    if val { "on" } else { "off" }
+}
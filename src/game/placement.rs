@@ -0,0 +1,158 @@
+use crate::{goals::Goals, maze::Maze};
+use multid::BoundedIx2;
+use rand::{Rng, seq::IndexedRandom};
+
+/// where the player starts and where the goal goes
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Placement {
+    /// start in the top left, goal in the bottom right
+    #[default]
+    TopLeft,
+    /// start and goal in two different random rooms
+    Random,
+    /// start in a random corner, goal in the opposite one
+    OppositeCorners,
+    /// start on a random edge, goal somewhere on the opposite edge
+    EdgeToEdge,
+    /// start and goal at the two ends of the longest path through the maze
+    FarthestPair,
+    /// start in a random room, goal as many moves away from it as possible
+    FarFromStart,
+}
+
+impl Placement {
+    pub const ALL: [Placement; 6] = [
+        Placement::TopLeft,
+        Placement::Random,
+        Placement::OppositeCorners,
+        Placement::EdgeToEdge,
+        Placement::FarthestPair,
+        Placement::FarFromStart,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Placement::TopLeft => "top left to bottom right",
+            Placement::Random => "random",
+            Placement::OppositeCorners => "opposite corners",
+            Placement::EdgeToEdge => "edge to edge",
+            Placement::FarthestPair => "farthest apart",
+            Placement::FarFromStart => "far from a random start",
+        }
+    }
+
+    /// placements that only depend on the shape of the maze happen before any doors are opened,
+    /// so seeders that carve from the start towards the goal join them up
+    pub fn place_before_carving<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+        &self,
+        maze: &mut Maze<N_ROWS, N_COLS>,
+        rng: &mut R,
+    ) {
+        let rooms: Vec<BoundedIx2<N_ROWS, N_COLS>> = maze.indices().collect();
+        let (start, goal) = match self {
+            Placement::TopLeft | Placement::FarthestPair | Placement::FarFromStart => return,
+            Placement::Random => {
+                let picked: Vec<_> = rooms.choose_multiple(rng, 2).copied().collect();
+                match picked[..] {
+                    [start, goal] => (start, goal),
+                    _ => return,
+                }
+            }
+            Placement::OppositeCorners => {
+                let corners = [
+                    (0, 0),
+                    (0, N_COLS - 1),
+                    (N_ROWS - 1, N_COLS - 1),
+                    (N_ROWS - 1, 0),
+                ];
+                let n = rng.random_range(0..corners.len());
+                (
+                    closest_room(&rooms, corners[n]),
+                    closest_room(&rooms, corners[(n + 2) % corners.len()]),
+                )
+            }
+            Placement::EdgeToEdge => {
+                // the outermost rooms on each side, which follow the outline of a shaped maze
+                let top = rooms.iter().map(|ix| ix.y()).min().unwrap_or(0);
+                let bottom = rooms.iter().map(|ix| ix.y()).max().unwrap_or(0);
+                let left = rooms.iter().map(|ix| ix.x()).min().unwrap_or(0);
+                let right = rooms.iter().map(|ix| ix.x()).max().unwrap_or(0);
+                let sides: [Vec<BoundedIx2<N_ROWS, N_COLS>>; 4] = [
+                    rooms.iter().filter(|ix| ix.y() == top).copied().collect(),
+                    rooms.iter().filter(|ix| ix.x() == right).copied().collect(),
+                    rooms
+                        .iter()
+                        .filter(|ix| ix.y() == bottom)
+                        .copied()
+                        .collect(),
+                    rooms.iter().filter(|ix| ix.x() == left).copied().collect(),
+                ];
+                let n = rng.random_range(0..sides.len());
+                match (
+                    sides[n].choose(rng),
+                    sides[(n + 2) % sides.len()].choose(rng),
+                ) {
+                    (Some(start), Some(goal)) if start != goal => (*start, *goal),
+                    _ => return,
+                }
+            }
+        };
+        maze.current_ix = start;
+        maze.goals = Goals::single(goal);
+    }
+
+    /// placements that depend on which doors are open happen after carving; they only pick rooms
+    /// that can be reached from wherever the seeder started
+    pub fn place_after_carving<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+        &self,
+        maze: &mut Maze<N_ROWS, N_COLS>,
+        rng: &mut R,
+    ) {
+        let reachable: Vec<BoundedIx2<N_ROWS, N_COLS>> = {
+            let dists = maze.distances_from(maze.current_ix);
+            maze.indices().filter(|ix| dists[*ix].is_some()).collect()
+        };
+        let Some(&random) = reachable.choose(rng) else {
+            return;
+        };
+        let (start, goal) = match self {
+            Placement::TopLeft
+            | Placement::Random
+            | Placement::OppositeCorners
+            | Placement::EdgeToEdge => return,
+            Placement::FarthestPair => {
+                let end = farthest_from(maze, random, rng);
+                (end, farthest_from(maze, end, rng))
+            }
+            Placement::FarFromStart => (random, farthest_from(maze, random, rng)),
+        };
+        if start != goal {
+            maze.current_ix = start;
+            maze.goals = Goals::single(goal);
+        }
+    }
+}
+
+fn closest_room<const N_ROWS: usize, const N_COLS: usize>(
+    rooms: &[BoundedIx2<N_ROWS, N_COLS>],
+    (row, col): (usize, usize),
+) -> BoundedIx2<N_ROWS, N_COLS> {
+    rooms
+        .iter()
+        .min_by_key(|ix| ix.y().abs_diff(row) + ix.x().abs_diff(col))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// one of the rooms that takes the most moves to reach from `ix`
+fn farthest_from<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+    maze: &Maze<N_ROWS, N_COLS>,
+    ix: BoundedIx2<N_ROWS, N_COLS>,
+    rng: &mut R,
+) -> BoundedIx2<N_ROWS, N_COLS> {
+    let dists = maze.distances_from(ix);
+    let max = maze.indices().filter_map(|i| dists[i]).max().unwrap_or(0);
+    let farthest: Vec<BoundedIx2<N_ROWS, N_COLS>> =
+        maze.indices().filter(|i| dists[*i] == Some(max)).collect();
+    farthest.choose(rng).copied().unwrap_or(ix)
+}
@@ -0,0 +1,129 @@
+use super::{Outcome, mode::GameMode, options::Options, seed_polar_doors};
+use crate::{
+    movement::MazeEvent,
+    polar::PolarMaze,
+    save::{Reader, Save, Token, Writer},
+    ui::{self, Breadcrumb, PolarRoomView, TrailView},
+};
+use color_eyre::Result;
+use rand::rngs::StdRng;
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    layout::Rect,
+    widgets::{StatefulWidget, Widget, canvas::Canvas},
+};
+
+pub struct PolarGame {
+    trail: TrailView,
+}
+
+impl StatefulWidget for PolarGame {
+    type State = PolarMaze;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let ring_height = ui::MAX_Y / state.n_rings() as f64;
+        let max_visits = state.max_visits();
+        let c = Canvas::default()
+            .x_bounds([ui::MIN_X, ui::MAX_X])
+            .y_bounds([ui::MIN_Y, ui::MAX_Y])
+            .background_color(ui::BG_COLOR)
+            .paint(move |ctx| {
+                for ix in state.indices() {
+                    let (start, end) = state.angles(ix);
+                    let view = PolarRoomView {
+                        inner: ring_height * ix.ring as f64,
+                        outer: ring_height * (ix.ring + 1) as f64,
+                        start,
+                        end,
+                        doors: &state.room(ix).doors,
+                    };
+                    ctx.draw(&view);
+                    let (cx, cy) = state.centre(ix);
+                    let label_x = cx * ring_height - ui::SEG_LEN;
+                    let label_y = cy * ring_height;
+                    // the breadcrumb sits below and left of the label, where it would in a square room
+                    ctx.draw(&Breadcrumb {
+                        x: label_x - ui::SEG_LEN * 2.0,
+                        y: label_y + ui::SEG_LEN * 3.0,
+                        visits: state.visits(ix),
+                        max_visits,
+                        view: self.trail,
+                    });
+                    if ix == state.current_ix && ix == state.goal {
+                        ctx.print(label_x, label_y, "\u{1f940}")
+                    } else if ix == state.current_ix {
+                        ctx.print(label_x, label_y, "\u{1f600}")
+                    } else if ix == state.goal {
+                        ctx.print(label_x, label_y, "\u{1f945}")
+                    }
+                }
+            });
+        Widget::render(c, area, buf);
+    }
+}
+
+/// a circular maze with nothing hidden
+pub struct PolarMode {
+    maze: PolarMaze,
+    trail: TrailView,
+}
+
+impl GameMode for PolarMode {
+    fn handle_event(&mut self, event: MazeEvent) {
+        match event {
+            MazeEvent::MoveN => self.maze.move_toward(0.0, 1.0),
+            MazeEvent::MoveS => self.maze.move_toward(0.0, -1.0),
+            MazeEvent::MoveE => self.maze.move_toward(1.0, 0.0),
+            MazeEvent::MoveW => self.maze.move_toward(-1.0, 0.0),
+            _ => false,
+        };
+    }
+    fn render(&mut self, frame: &mut Frame) {
+        frame.render_stateful_widget(
+            PolarGame { trail: self.trail },
+            frame.area(),
+            &mut self.maze,
+        )
+    }
+    fn outcome(&self) -> Option<Outcome> {
+        self.maze.is_done().then_some(Outcome::Win)
+    }
+    fn trail(&self) -> ([&'static str; 2], Vec<(usize, usize)>) {
+        (
+            ["ring", "cell"],
+            self.maze
+                .trail
+                .iter()
+                .map(|ix| (ix.ring, ix.cell))
+                .collect(),
+        )
+    }
+    fn position(&self) -> (usize, usize) {
+        (self.maze.current_ix.ring, self.maze.current_ix.cell)
+    }
+    fn save(&self, out: &mut Writer) {
+        self.maze.save(out);
+        out.field("trail_view", self.trail.token());
+    }
+}
+
+/// the polar maze has as many rings as the grid has rows, and ignores the other options
+pub fn new_game<const N_ROWS: usize, const N_COLS: usize>(
+    rng: &mut StdRng,
+    options: &Options,
+) -> Result<Box<dyn GameMode>> {
+    let mut maze = PolarMaze::new(N_ROWS);
+    seed_polar_doors(&mut maze, rng);
+    Ok(Box::new(PolarMode {
+        maze,
+        trail: options.trail,
+    }))
+}
+
+pub fn load(input: &mut Reader) -> Result<Box<dyn GameMode>> {
+    Ok(Box::new(PolarMode {
+        maze: PolarMaze::load(input)?,
+        trail: input.token("trail_view")?,
+    }))
+}
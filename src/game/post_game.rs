@@ -0,0 +1,103 @@
+use crate::{events::EventSource, movement::MazeEvent};
+use color_eyre::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame, Terminal,
+    backend::Backend,
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    widgets::{Block, List, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+/// what to do once a maze has been won
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AfterGame {
+    /// play the same maze again from the start
+    Retry,
+    /// play a new maze with the same settings
+    New,
+    /// back to the menu
+    Menu,
+}
+
+const CHOICES: [AfterGame; 3] = [AfterGame::Retry, AfterGame::New, AfterGame::Menu];
+
+impl AfterGame {
+    fn label(&self) -> &'static str {
+        match self {
+            AfterGame::Retry => "Retry this maze (r)",
+            AfterGame::New => "New maze (n)",
+            AfterGame::Menu => "Menu (q)",
+        }
+    }
+}
+
+/// how the game went, and what to do next
+pub struct PostGame<'a> {
+    pub msg: &'a str,
+}
+
+impl<'a> StatefulWidget for PostGame<'a> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let b = Block::bordered()
+            .title_alignment(Alignment::Center)
+            .title("Game over")
+            .fg(Color::Green)
+            .padding(Padding::symmetric(5, 1));
+        let inner_area = b.inner(area);
+        let [msg_area, list_area] =
+            Layout::vertical([Constraint::Length(5), Constraint::Min(0)]).areas(inner_area);
+        let l = List::new(CHOICES.iter().map(AfterGame::label))
+            .block(Block::bordered())
+            .fg(Color::Green)
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("*");
+        Widget::render(b, area, buf);
+        Widget::render(
+            Paragraph::new(self.msg)
+                .alignment(Alignment::Center)
+                .block(Block::bordered())
+                .fg(Color::Green),
+            msg_area,
+            buf,
+        );
+        StatefulWidget::render(l, list_area, buf, state);
+    }
+}
+
+/// show `msg` and wait for the player to pick what to do next
+pub fn choose<B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut dyn EventSource,
+    msg: &str,
+) -> Result<AfterGame> {
+    let mut list = ListState::default();
+    list.select_first();
+    loop {
+        terminal.draw(|frame: &mut Frame| {
+            frame.render_stateful_widget(PostGame { msg }, frame.area(), &mut list)
+        })?;
+        let Some(event) = events.next_event()? else {
+            return Ok(AfterGame::Menu);
+        };
+        match event.into() {
+            MazeEvent::MoveN => list.select_previous(),
+            MazeEvent::MoveS => list.select_next(),
+            MazeEvent::Enter => {
+                return Ok(list
+                    .selected()
+                    .and_then(|n| CHOICES.get(n).copied())
+                    .unwrap_or(AfterGame::Menu));
+            }
+            MazeEvent::Restart => return Ok(AfterGame::Retry),
+            MazeEvent::NewMaze => return Ok(AfterGame::New),
+            MazeEvent::Quit | MazeEvent::OtherKey(KeyCode::Char('m')) => {
+                return Ok(AfterGame::Menu);
+            }
+            _ => (),
+        }
+    }
+}
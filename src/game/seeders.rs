@@ -1,13 +1,19 @@
-use crate::{Direction, maze::Maze};
-use multid::{BoundedIx2, iterators::V2Indices};
-use rand::{Rng, rngs::ThreadRng, seq::IndexedRandom};
+use crate::{
+    Direction, DirectionsIter,
+    maze::{Axis, DoorState, Item, Maze, RoomKind},
+    polar::{PolarIx, PolarMaze},
+};
+use multid::BoundedIx2;
+use rand::{Rng, seq::IndexedRandom};
 use std::collections::BTreeSet;
-pub fn seed_doors_naive<const N_ROWS: usize, const N_COLS: usize>(
+pub fn seed_doors_naive<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
     maze: &mut Maze<N_ROWS, N_COLS>,
-    rng: &mut ThreadRng,
+    rng: &mut R,
 ) {
-    for ix in V2Indices::<N_ROWS, N_COLS>::new() {
-        while !maze.rooms[ix].doors.any_open() {
+    let ixs: Vec<BoundedIx2<N_ROWS, N_COLS>> = maze.indices().collect();
+    for ix in ixs {
+        // a room on its own in the shape has no doors to open
+        while maze.rooms[ix].all_doors().next().is_some() && !maze.rooms[ix].doors.any_open() {
             if rng.random_bool(0.5) {
                 maze.open_north(ix);
             }
@@ -24,50 +30,29 @@ pub fn seed_doors_naive<const N_ROWS: usize, const N_COLS: usize>(
     }
 }
 
-pub fn seed_doors_path<const N_ROWS: usize, const N_COLS: usize>(
+pub fn seed_doors_path<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
     maze: &mut Maze<N_ROWS, N_COLS>,
-    rng: &mut ThreadRng,
+    rng: &mut R,
 ) {
     let mut all_visited: BTreeSet<BoundedIx2<N_ROWS, N_COLS>> = BTreeSet::new();
     'outer: loop {
         let mut visited: BTreeSet<BoundedIx2<N_ROWS, N_COLS>> = BTreeSet::new();
         let mut curr: BoundedIx2<N_ROWS, N_COLS> = maze.current_ix;
         loop {
-            if curr == maze.goal {
+            if curr == maze.goals.last() {
                 break 'outer;
             }
             visited.insert(curr);
             let available: Vec<Direction> = maze.rooms[curr]
                 .all_doors()
-                .filter_map::<Direction, _>(|(dir, _)| match dir {
-                    Direction::North => curr.north().and_then(|ix| {
+                .filter_map::<Direction, _>(|(dir, _)| {
+                    maze.neighbor(curr, dir).and_then(|ix| {
                         if visited.contains(&ix) {
                             None
                         } else {
                             Some(dir)
                         }
-                    }),
-                    Direction::East => curr.east().and_then(|ix| {
-                        if visited.contains(&ix) {
-                            None
-                        } else {
-                            Some(dir)
-                        }
-                    }),
-                    Direction::South => curr.south().and_then(|ix| {
-                        if visited.contains(&ix) {
-                            None
-                        } else {
-                            Some(dir)
-                        }
-                    }),
-                    Direction::West => curr.west().and_then(|ix| {
-                        if visited.contains(&ix) {
-                            None
-                        } else {
-                            Some(dir)
-                        }
-                    }),
+                    })
                 })
                 .collect();
             match available.choose(rng) {
@@ -77,24 +62,25 @@ pub fn seed_doors_path<const N_ROWS: usize, const N_COLS: usize>(
                 }
                 Some(Direction::North) => {
                     maze.open_north(curr);
-                    curr = curr.north().unwrap()
+                    curr = maze.neighbor(curr, Direction::North).unwrap()
                 }
                 Some(Direction::East) => {
                     maze.open_east(curr);
-                    curr = curr.east().unwrap()
+                    curr = maze.neighbor(curr, Direction::East).unwrap()
                 }
                 Some(Direction::South) => {
                     maze.open_south(curr);
-                    curr = curr.south().unwrap()
+                    curr = maze.neighbor(curr, Direction::South).unwrap()
                 }
                 Some(Direction::West) => {
                     maze.open_west(curr);
-                    curr = curr.west().unwrap()
+                    curr = maze.neighbor(curr, Direction::West).unwrap()
                 }
             }
         }
     }
-    for ix in V2Indices::<N_ROWS, N_COLS>::new() {
+    let ixs: Vec<BoundedIx2<N_ROWS, N_COLS>> = maze.indices().collect();
+    for ix in ixs {
         if !all_visited.contains(&ix) {
             match maze.rooms[ix]
                 .available_directions()
@@ -110,3 +96,183 @@ pub fn seed_doors_path<const N_ROWS: usize, const N_COLS: usize>(
         }
     }
 }
+
+/// carve a perfect maze (exactly one path between any two rooms) with a randomized depth-first
+/// search from the start
+pub fn seed_doors_backtrack<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+    maze: &mut Maze<N_ROWS, N_COLS>,
+    rng: &mut R,
+) {
+    carve_backtrack(maze, rng, false)
+}
+
+/// like [`seed_doors_backtrack`], but the search can also tunnel under a straight corridor to
+/// reach an unvisited room on the far side, leaving a crossing behind
+pub fn seed_doors_weave<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+    maze: &mut Maze<N_ROWS, N_COLS>,
+    rng: &mut R,
+) {
+    carve_backtrack(maze, rng, true)
+}
+
+fn carve_backtrack<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+    maze: &mut Maze<N_ROWS, N_COLS>,
+    rng: &mut R,
+    weave: bool,
+) {
+    let mut visited: BTreeSet<BoundedIx2<N_ROWS, N_COLS>> = BTreeSet::from([maze.current_ix]);
+    let mut stack: Vec<BoundedIx2<N_ROWS, N_COLS>> = vec![maze.current_ix];
+    while let Some(&curr) = stack.last() {
+        // (direction, room to carve into, room tunnelled under on the way)
+        let mut available: Vec<(Direction, BoundedIx2<N_ROWS, N_COLS>, Option<_>)> = Vec::new();
+        for (dir, _) in maze.rooms[curr].all_doors() {
+            let Some(next) = maze.neighbor(curr, dir) else {
+                continue;
+            };
+            if !visited.contains(&next) {
+                available.push((dir, next, None));
+            } else if weave
+                && can_tunnel_under(maze, next, dir)
+                && let Some(beyond) = maze.neighbor(next, dir).filter(|ix| !visited.contains(ix))
+            {
+                available.push((dir, beyond, Some(next)));
+            }
+        }
+        match available.choose(rng) {
+            None => {
+                stack.pop();
+            }
+            Some(&(dir, next, under)) => {
+                maze.open_dir(curr, dir);
+                if let Some(under) = under {
+                    maze.open_dir(under, dir);
+                    maze.rooms[under].kind = RoomKind::Crossing {
+                        over: Axis::of(dir).perpendicular(),
+                    };
+                }
+                visited.insert(next);
+                stack.push(next);
+            }
+        }
+    }
+}
+
+/// whether a corridor heading `dir` can pass under the room at `ix`: it has to be a plain
+/// corridor running straight across `dir`, and not somewhere the player starts or stops
+fn can_tunnel_under<const N_ROWS: usize, const N_COLS: usize>(
+    maze: &Maze<N_ROWS, N_COLS>,
+    ix: BoundedIx2<N_ROWS, N_COLS>,
+    dir: Direction,
+) -> bool {
+    let room = &maze.rooms[ix];
+    if room.is_crossing() || ix == maze.current_ix || maze.goals.contains(ix) {
+        return false;
+    }
+    let across = Axis::of(dir).perpendicular();
+    DirectionsIter::new().all(|d| {
+        let open = room.doors.get(d) == Some(DoorState::Open);
+        open == (Axis::of(d) == across)
+    })
+}
+
+/// add `n` checkpoints in front of the last goal, spread evenly along the way to it: the `i`th
+/// one goes in a room `i / (n + 1)` of the way there, counting moves from the start
+pub fn place_checkpoints<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+    maze: &mut Maze<N_ROWS, N_COLS>,
+    n: usize,
+    rng: &mut R,
+) {
+    let dists = maze.distances_from(maze.current_ix);
+    let Some(total) = dists[maze.goals.last()] else {
+        return;
+    };
+    for i in 1..=n {
+        let target = total * i / (n + 1);
+        let candidates: Vec<(usize, BoundedIx2<N_ROWS, N_COLS>)> = maze
+            .indices()
+            .filter(|ix| *ix != maze.current_ix && !maze.goals.contains(*ix))
+            .filter_map(|ix| dists[ix].map(|d| (d.abs_diff(target), ix)))
+            .collect();
+        let Some(closest) = candidates.iter().map(|(off, _)| *off).min() else {
+            return;
+        };
+        let best: Vec<BoundedIx2<N_ROWS, N_COLS>> = candidates
+            .iter()
+            .filter(|(off, _)| *off == closest)
+            .map(|(_, ix)| *ix)
+            .collect();
+        if let Some(ix) = best.choose(rng) {
+            maze.goals.insert_checkpoint(*ix);
+        }
+    }
+}
+
+/// put `n` items in random rooms, about one in five of them gems and the rest coins; the start,
+/// goals and crossings are left empty
+pub fn scatter_items<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+    maze: &mut Maze<N_ROWS, N_COLS>,
+    n: usize,
+    rng: &mut R,
+) {
+    for ix in empty_rooms(maze).choose_multiple(rng, n) {
+        maze.rooms[*ix].item = Some(if rng.random_bool(0.2) {
+            Item::Gem
+        } else {
+            Item::Coin
+        });
+    }
+}
+
+/// put `n` cans of lantern fuel in random empty rooms
+pub fn scatter_fuel<const N_ROWS: usize, const N_COLS: usize, R: Rng + ?Sized>(
+    maze: &mut Maze<N_ROWS, N_COLS>,
+    n: usize,
+    rng: &mut R,
+) {
+    for ix in empty_rooms(maze).choose_multiple(rng, n) {
+        maze.rooms[*ix].item = Some(Item::Fuel);
+    }
+}
+
+/// rooms the player can get to with nothing in them that aren't the start, a goal or a
+/// crossing
+fn empty_rooms<const N_ROWS: usize, const N_COLS: usize>(
+    maze: &Maze<N_ROWS, N_COLS>,
+) -> Vec<BoundedIx2<N_ROWS, N_COLS>> {
+    let dists = maze.distances_from(maze.current_ix);
+    maze.indices()
+        .filter(|ix| {
+            dists[*ix].is_some()
+                && *ix != maze.current_ix
+                && !maze.goals.contains(*ix)
+                && !maze.rooms[*ix].is_crossing()
+                && maze.rooms[*ix].item.is_none()
+        })
+        .collect()
+}
+
+/// carve a circular maze with a randomized depth-first search from the centre, then start the
+/// player at a random room on the rim
+pub fn seed_polar_doors<R: Rng + ?Sized>(maze: &mut PolarMaze, rng: &mut R) {
+    let mut visited: BTreeSet<PolarIx> = BTreeSet::new();
+    let mut stack: Vec<PolarIx> = vec![maze.goal];
+    visited.insert(maze.goal);
+    while let Some(&curr) = stack.last() {
+        let unvisited: Vec<_> = maze
+            .neighbors(curr)
+            .filter(|(_, ix)| !visited.contains(ix))
+            .collect();
+        match unvisited.choose(rng) {
+            None => {
+                stack.pop();
+            }
+            Some(&(dir, next)) => {
+                maze.open(curr, dir);
+                visited.insert(next);
+                stack.push(next);
+            }
+        }
+    }
+    let rim = maze.n_rings() - 1;
+    maze.current_ix = PolarIx::new(rim, rng.random_range(0..maze.ring_len(rim)));
+}
@@ -0,0 +1,22 @@
+use color_eyre::{Result, eyre::WrapErr};
+use std::{fmt::Write, fs, path::PathBuf};
+
+/// directory trails are saved to at the end of a game
+pub const TRAIL_DIR: &str = "trails";
+
+/// write the rooms the player went through to `<TRAIL_DIR>/<name>.csv`, one row per step,
+/// with `columns` naming the two numbers that pick out each room
+pub fn save(
+    name: &str,
+    columns: [&str; 2],
+    rooms: impl IntoIterator<Item = (usize, usize)>,
+) -> Result<PathBuf> {
+    let mut csv = format!("step,{},{}\n", columns[0], columns[1]);
+    for (step, (a, b)) in rooms.into_iter().enumerate() {
+        writeln!(csv, "{step},{a},{b}")?;
+    }
+    fs::create_dir_all(TRAIL_DIR).wrap_err_with(|| format!("couldn't create {TRAIL_DIR}"))?;
+    let path = PathBuf::from(TRAIL_DIR).join(format!("{name}.csv"));
+    fs::write(&path, csv).wrap_err_with(|| format!("couldn't write {}", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,161 @@
+use crate::save::{self, Reader, Save, Token, Writer};
+use color_eyre::{Result, eyre::bail};
+use multid::BoundedIx2;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WinCondition {
+    /// reaching any one goal wins
+    #[default]
+    Any,
+    /// every goal has to be reached, in any order
+    All,
+    /// the goals are checkpoints that have to be reached one after another
+    Ordered,
+}
+
+/// where the player is trying to get to, and how far they've got
+#[derive(Debug, Clone, PartialEq)]
+pub struct Goals<const N_ROWS: usize, const N_COLS: usize> {
+    ixs: Vec<BoundedIx2<N_ROWS, N_COLS>>,
+    reached: Vec<bool>,
+    pub condition: WinCondition,
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Goals<N_ROWS, N_COLS> {
+    pub fn single(ix: BoundedIx2<N_ROWS, N_COLS>) -> Self {
+        Self {
+            ixs: vec![ix],
+            reached: vec![false],
+            condition: WinCondition::Any,
+        }
+    }
+    /// goals in order with whether each has been reached already
+    pub fn with_reached(
+        goals: impl IntoIterator<Item = (BoundedIx2<N_ROWS, N_COLS>, bool)>,
+        condition: WinCondition,
+    ) -> Result<Self> {
+        let (ixs, reached): (Vec<_>, Vec<_>) = goals.into_iter().unzip();
+        if ixs.is_empty() {
+            bail!("no goals");
+        }
+        Ok(Self {
+            ixs,
+            reached,
+            condition,
+        })
+    }
+    pub fn iter(&self) -> impl Iterator<Item = BoundedIx2<N_ROWS, N_COLS>> + '_ {
+        self.ixs.iter().copied()
+    }
+    pub fn len(&self) -> usize {
+        self.ixs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ixs.is_empty()
+    }
+    /// the goal the seeders carve towards; checkpoints go in front of it
+    pub fn last(&self) -> BoundedIx2<N_ROWS, N_COLS> {
+        *self.ixs.last().unwrap()
+    }
+    /// add a checkpoint just before the last goal
+    pub fn insert_checkpoint(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
+        let at = self.ixs.len() - 1;
+        self.ixs.insert(at, ix);
+        self.reached.insert(at, false);
+    }
+    pub fn contains(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> bool {
+        self.ixs.contains(&ix)
+    }
+    /// position of `ix` in the list of goals, if it's one of them
+    pub fn position(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> Option<usize> {
+        self.ixs.iter().position(|g| *g == ix)
+    }
+    pub fn is_reached(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> bool {
+        self.position(ix).is_some_and(|n| self.reached[n])
+    }
+    /// the checkpoint that has to be reached next, when they have to be reached in order
+    pub fn next(&self) -> Option<BoundedIx2<N_ROWS, N_COLS>> {
+        self.reached.iter().position(|r| !r).map(|n| self.ixs[n])
+    }
+    /// tick off `ix` if it's a goal that counts right now
+    pub fn visit(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
+        let n = match self.condition {
+            WinCondition::Ordered => self
+                .reached
+                .iter()
+                .position(|r| !r)
+                .filter(|n| self.ixs[*n] == ix),
+            WinCondition::Any | WinCondition::All => self.position(ix),
+        };
+        if let Some(n) = n {
+            self.reached[n] = true;
+        }
+    }
+    pub fn n_reached(&self) -> usize {
+        self.reached.iter().filter(|r| **r).count()
+    }
+    pub fn is_done(&self) -> bool {
+        match self.condition {
+            WinCondition::Any => self.reached.iter().any(|r| *r),
+            WinCondition::All | WinCondition::Ordered => self.reached.iter().all(|r| *r),
+        }
+    }
+    /// a short description of how far the player has got, for the HUD
+    pub fn progress(&self) -> String {
+        match self.condition {
+            WinCondition::Any if self.len() == 1 => "reach the goal".into(),
+            WinCondition::Any => format!("reach any of {} goals", self.len()),
+            WinCondition::All => format!("goals {}/{}", self.n_reached(), self.len()),
+            WinCondition::Ordered => {
+                format!("checkpoints {}/{} in order", self.n_reached(), self.len())
+            }
+        }
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Save for Goals<N_ROWS, N_COLS> {
+    /// each goal as `row,col,reached` with reached `1` or `0`
+    fn save(&self, out: &mut Writer) {
+        out.field("win", self.condition.token());
+        out.list(
+            "goals",
+            self.ixs
+                .iter()
+                .zip(&self.reached)
+                .map(|(ix, reached)| format!("{},{}", save::ix(*ix), *reached as u8)),
+        );
+    }
+    fn load(input: &mut Reader) -> Result<Self> {
+        let condition = input.token("win")?;
+        let goals = input.list("goals", |s| {
+            let (ix, reached) = save::parse_ix(s)?;
+            match reached {
+                "0" => Some((ix, false)),
+                "1" => Some((ix, true)),
+                _ => None,
+            }
+        })?;
+        Self::with_reached(goals, condition)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ordered() {
+        let a = BoundedIx2::<3, 3>::new(0, 1).unwrap();
+        let b = BoundedIx2::<3, 3>::new(2, 2).unwrap();
+        let mut goals = Goals::single(b);
+        goals.insert_checkpoint(a);
+        goals.condition = WinCondition::Ordered;
+        goals.visit(b);
+        assert_eq!(0, goals.n_reached(), "out of order");
+        goals.visit(a);
+        goals.visit(b);
+        assert!(goals.is_done());
+    }
+}
@@ -1,15 +1,24 @@
 #![allow(incomplete_features)]
 #![feature(generic_const_exprs)]
 
+pub mod chalk;
+pub mod events;
+pub mod format;
 pub mod game;
+pub mod goals;
+pub mod mask;
 pub mod maze;
 pub mod movement;
+pub mod polar;
+pub mod save;
 pub mod ui;
 
 pub use game::game_loop;
 pub use maze::Maze;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Direction {
     North,
     South,
@@ -17,6 +26,26 @@ pub enum Direction {
     West,
 }
 
+impl Direction {
+    /// (row, column) step to the next room in this direction
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::South => (1, 0),
+            Direction::East => (0, 1),
+            Direction::West => (0, -1),
+        }
+    }
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+}
+
 impl IntoIterator for Direction {
     type Item = Self;
     type IntoIter = DirectionsIter;
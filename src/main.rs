@@ -1,7 +1,94 @@
-use color_eyre::Result;
-use samazing::*;
+use color_eyre::{Result, eyre::bail};
+use rand::{SeedableRng, rngs::StdRng};
+use samazing::{
+    format::{
+        dot,
+        ppm::{self, RasterOptions},
+        svg::{self, SvgOptions},
+    },
+    game::options::Options,
+    *,
+};
+use std::{
+    env,
+    io::{self, Write},
+};
+
+const USAGE: &str = "usage: samazing [dump [--json | --svg [--solution] | --ppm [--scale=N] \
+                     | --dot] [--print] [seed]]";
 
 fn main() -> Result<()> {
     color_eyre::install()?;
-    game_loop::<7, 7>()
+    let (flags, args): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let flag = |name: &str| flags.iter().any(|f| f == name);
+    let known = ["--json", "--svg", "--ppm", "--dot", "--solution", "--print"];
+    let mut room_px = ppm::DEFAULT_ROOM_PX;
+    for f in &flags {
+        if let Some(scale) = f.strip_prefix("--scale=") {
+            match scale.parse() {
+                Ok(scale) => room_px = scale,
+                Err(_) => bail!("bad scale {scale:?}\n{USAGE}"),
+            }
+        } else if !known.contains(&f.as_str()) {
+            bail!("unknown option {f}\n{USAGE}");
+        }
+    }
+    let formats = ["--json", "--svg", "--ppm", "--dot"];
+    let format = match formats.into_iter().filter(|f| flag(f)).collect::<Vec<_>>()[..] {
+        [] => Format::Text,
+        ["--json"] => Format::Json,
+        ["--svg"] => Format::Svg(SvgOptions {
+            solution: flag("--solution"),
+            trail: false,
+            print: flag("--print"),
+        }),
+        ["--ppm"] => Format::Ppm(RasterOptions {
+            room_px,
+            print: flag("--print"),
+        }),
+        ["--dot"] => Format::Dot,
+        _ => bail!("pick one of {}\n{USAGE}", formats.join(", ")),
+    };
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] if flags.is_empty() => game_loop::<7, 7>(),
+        ["dump"] => dump(rand::random(), format),
+        ["dump", seed] => match seed.parse() {
+            Ok(seed) => dump(seed, format),
+            Err(_) => bail!("bad seed {seed:?}\n{USAGE}"),
+        },
+        _ => bail!(USAGE),
+    }
+}
+
+/// how to print a maze
+enum Format {
+    Text,
+    Json,
+    Svg(SvgOptions),
+    Ppm(RasterOptions),
+    Dot,
+}
+
+/// print the maze the menu's default options make from `seed`
+fn dump(seed: u64, format: Format) -> Result<()> {
+    let maze: Maze<7, 7> = game::new_seeded(&mut StdRng::seed_from_u64(seed), &Options::default())?;
+    match format {
+        Format::Text => {
+            println!("# seed {seed}");
+            print!("{maze}");
+        }
+        #[cfg(feature = "serde")]
+        Format::Json => println!("{}", format::json::to_json(&maze)?),
+        #[cfg(not(feature = "serde"))]
+        Format::Json => {
+            bail!("json needs the serde feature: cargo run --features serde -- dump --json")
+        }
+        Format::Svg(options) => print!("{}", svg::to_svg(&maze, &options)),
+        Format::Dot => print!("{}", dot::to_dot(&maze)),
+        Format::Ppm(options) => {
+            io::stdout().write_all(&ppm::rasterise(&maze, &options).to_ppm())?
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,160 @@
+use color_eyre::{Result, eyre::bail};
+use multid::{
+    BoundedIx2, V2,
+    iterators::{Ix2CardinalNeighbors, V2Indices},
+};
+use std::{collections::BTreeSet, fs, path::Path, str::FromStr};
+
+/// directory shape files are loaded from
+pub const MASK_DIR: &str = "masks";
+
+/// the footprint of a maze: which cells are rooms and which are empty space
+///
+/// written as text with one line per row, `#` for a room and `.` for a gap, e.g.
+///
+/// ```text
+/// .##.##.
+/// #######
+/// .#####.
+/// ..###..
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mask<const N_ROWS: usize, const N_COLS: usize> {
+    cells: V2<bool, N_ROWS, N_COLS>,
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Mask<N_ROWS, N_COLS> {
+    /// every cell is a room
+    pub fn full() -> Self {
+        Self {
+            cells: V2::new(vec![true; N_ROWS * N_COLS]).unwrap(),
+        }
+    }
+    /// load `name` from the shapes directory
+    pub fn load(name: &str) -> Result<Self> {
+        Self::from_file(Path::new(MASK_DIR).join(format!("{name}.txt")))
+    }
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(s) => s.parse(),
+            Err(e) => bail!("couldn't read {}: {e}", path.display()),
+        }
+    }
+    pub fn is_room(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> bool {
+        self.cells[ix]
+    }
+    pub fn rooms(&self) -> impl Iterator<Item = BoundedIx2<N_ROWS, N_COLS>> + '_ {
+        V2Indices::<N_ROWS, N_COLS>::new().filter(|ix| self.is_room(*ix))
+    }
+    /// every room has to be reachable from every other one, or the seeders can't finish
+    fn check_connected(&self) -> Result<()> {
+        let Some(first) = self.rooms().next() else {
+            bail!("shape has no rooms");
+        };
+        let mut seen: BTreeSet<BoundedIx2<N_ROWS, N_COLS>> = BTreeSet::from([first]);
+        let mut todo = vec![first];
+        while let Some(ix) = todo.pop() {
+            for n in Ix2CardinalNeighbors::new(ix) {
+                if self.is_room(n) && seen.insert(n) {
+                    todo.push(n);
+                }
+            }
+        }
+        match self.rooms().find(|ix| !seen.contains(ix)) {
+            Some(ix) => bail!("room {ix} can't be reached from room {first}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> Default for Mask<N_ROWS, N_COLS> {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+impl<const N_ROWS: usize, const N_COLS: usize> FromStr for Mask<N_ROWS, N_COLS> {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let rows: Vec<&str> = s
+            .lines()
+            .map(str::trim_end)
+            .filter(|l| !l.is_empty())
+            .collect();
+        if rows.len() != N_ROWS {
+            bail!("expected {N_ROWS} rows, got {}", rows.len());
+        }
+        let mut cells: Vec<bool> = Vec::with_capacity(N_ROWS * N_COLS);
+        for (row_ix, row) in rows.iter().enumerate() {
+            let n_cols = row.chars().count();
+            if n_cols != N_COLS {
+                bail!("row {row_ix}: expected {N_COLS} columns, got {n_cols}");
+            }
+            for (col_ix, c) in row.chars().enumerate() {
+                match c {
+                    '#' => cells.push(true),
+                    '.' => cells.push(false),
+                    other => {
+                        bail!("row {row_ix}, column {col_ix}: expected '#' or '.', got {other:?}")
+                    }
+                }
+            }
+        }
+        let mask = Self {
+            cells: V2::new(cells).unwrap(),
+        };
+        mask.check_connected()?;
+        Ok(mask)
+    }
+}
+
+/// names of the shapes in the shapes directory, in alphabetical order
+pub fn available() -> Vec<String> {
+    txt_files(MASK_DIR)
+}
+
+/// names of the `.txt` files in `dir`, without the extension, in alphabetical order
+pub(crate) fn txt_files(dir: impl AsRef<Path>) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? == "txt" {
+                Some(path.file_stem()?.to_string_lossy().into_owned())
+            } else {
+                None
+            }
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let m: Mask<2, 3> = "#.#\n###\n".parse().unwrap();
+        assert!(m.is_room(BoundedIx2::new(0, 0).unwrap()));
+        assert!(!m.is_room(BoundedIx2::new(0, 1).unwrap()));
+        assert_eq!(5, m.rooms().count());
+    }
+
+    #[test]
+    fn test_bundled_shapes() {
+        for name in available() {
+            assert!(Mask::<7, 7>::load(&name).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_parse_disconnected() {
+        assert!("#.#\n#.#\n".parse::<Mask<2, 3>>().is_err());
+    }
+}
@@ -1,12 +1,25 @@
-use crate::{Direction, DirectionsIter};
+use crate::{
+    Direction, DirectionsIter,
+    goals::{Goals, WinCondition},
+    mask::Mask,
+    save::{self, Reader, Save, Token, Writer},
+};
+use color_eyre::{
+    Result,
+    eyre::{bail, eyre},
+};
 use multid::{BoundedIx2, V2, iterators};
+use std::collections::VecDeque;
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum DoorState {
     Open,
     Closed,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Doors {
     pub north: Option<DoorState>,
     pub east: Option<DoorState>,
@@ -55,6 +68,22 @@ impl Doors {
             self.west = Some(DoorState::Closed)
         }
     }
+    pub fn get(&self, dir: Direction) -> Option<DoorState> {
+        match dir {
+            Direction::North => self.north,
+            Direction::East => self.east,
+            Direction::South => self.south,
+            Direction::West => self.west,
+        }
+    }
+    fn get_mut(&mut self, dir: Direction) -> &mut Option<DoorState> {
+        match dir {
+            Direction::North => &mut self.north,
+            Direction::East => &mut self.east,
+            Direction::South => &mut self.south,
+            Direction::West => &mut self.west,
+        }
+    }
     pub fn any_open(&self) -> bool {
         for (_, st) in self {
             if st == DoorState::Open {
@@ -117,10 +146,68 @@ impl<'a> Iterator for DoorsIter<'a> {
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Axis {
+    NorthSouth,
+    EastWest,
+}
+
+impl Axis {
+    pub fn of(dir: Direction) -> Self {
+        match dir {
+            Direction::North | Direction::South => Axis::NorthSouth,
+            Direction::East | Direction::West => Axis::EastWest,
+        }
+    }
+    pub fn perpendicular(self) -> Self {
+        match self {
+            Axis::NorthSouth => Axis::EastWest,
+            Axis::EastWest => Axis::NorthSouth,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RoomKind {
+    #[default]
+    Plain,
+    /// one corridor bridges over another; there's nowhere to stop or turn, so anyone coming
+    /// in one side goes straight out the other
+    Crossing { over: Axis },
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Item {
+    Coin,
+    Gem,
+    /// lamp oil for the lantern; it isn't worth anything and doesn't count as collected
+    Fuel,
+}
+
+impl Item {
+    pub fn value(&self) -> usize {
+        match self {
+            Item::Coin => 1,
+            Item::Gem => 5,
+            Item::Fuel => 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Room {
     pub description: String,
     pub doors: Doors,
+    pub kind: RoomKind,
+    /// picked up by the first player to walk in
+    pub item: Option<Item>,
 }
 
 impl Room {
@@ -136,123 +223,492 @@ impl Room {
     pub fn available_directions(&self) -> impl Iterator<Item = Direction> {
         self.all_doors().map(|(dir, _)| dir)
     }
+    pub fn is_crossing(&self) -> bool {
+        matches!(self.kind, RoomKind::Crossing { .. })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Maze<const N_ROWS: usize, const N_COLS: usize> {
     pub rooms: V2<Room, N_ROWS, N_COLS>,
     pub current_ix: BoundedIx2<N_ROWS, N_COLS>,
-    pub goal: BoundedIx2<N_ROWS, N_COLS>,
+    pub goals: Goals<N_ROWS, N_COLS>,
+    /// whether doors on the edges lead around to the opposite edge
+    pub wrap: bool,
+    /// which cells are rooms; the rest are empty space with no doors in or out
+    pub mask: Mask<N_ROWS, N_COLS>,
+    /// total value of the items picked up so far
+    pub score: usize,
+    /// number of items picked up so far
+    pub collected: usize,
+    /// number of items that have to be picked up before reaching a goal counts
+    pub items_required: usize,
+    /// whatever was picked up on the last move
+    pub last_pickup: Option<Item>,
+    /// every room the player has stopped in, in order, starting from where they started; empty
+    /// until they first move
+    pub trail: Vec<BoundedIx2<N_ROWS, N_COLS>>,
+    /// rooms the player has moved out of, most recent last, so moves can be undone
+    history: Vec<BoundedIx2<N_ROWS, N_COLS>>,
+    /// undos the player has left, if there's a limit
+    pub undos_left: Option<usize>,
+    /// score lost for each undo
+    pub undo_cost: usize,
 }
 
 impl<const N_ROWS: usize, const N_COLS: usize> Maze<N_ROWS, N_COLS> {
     pub fn new() -> Self {
-        let ixs = iterators::V2Indices::<N_ROWS, N_COLS>::new();
+        Self::with_mask(Mask::full(), false)
+    }
+    /// a maze whose edges wrap around, so it's really the surface of a torus
+    ///
+    /// the goal is put as far away from the start as it can be, which is the middle rather
+    /// than the opposite corner (that's only a couple of steps away across the edges)
+    pub fn new_toroidal() -> Self {
+        Self::with_mask(Mask::full(), true)
+    }
+    /// a maze with rooms only where `mask` has them, starting in the first room and ending in
+    /// the last one
+    pub fn with_mask(mask: Mask<N_ROWS, N_COLS>, wrap: bool) -> Self {
         let mut rooms: Vec<Room> = Vec::with_capacity(N_ROWS * N_COLS);
-        for ix in ixs {
-            let r = Room {
-                description: format!("room {ix:?}"),
-                doors: Doors {
-                    north: ix.north().map(|_| DoorState::Closed),
-                    east: ix.east().map(|_| DoorState::Closed),
-                    south: ix.south().map(|_| DoorState::Closed),
-                    west: ix.west().map(|_| DoorState::Closed),
-                },
+        for ix in iterators::V2Indices::<N_ROWS, N_COLS>::new() {
+            let door = |dir| neighbor(ix, dir, wrap, &mask).map(|_| DoorState::Closed);
+            let r = if mask.is_room(ix) {
+                Room {
+                    description: format!("room {ix:?}"),
+                    doors: Doors {
+                        north: door(Direction::North),
+                        east: door(Direction::East),
+                        south: door(Direction::South),
+                        west: door(Direction::West),
+                    },
+                    kind: RoomKind::Plain,
+                    item: None,
+                }
+            } else {
+                Room {
+                    description: String::new(),
+                    doors: Doors {
+                        north: None,
+                        east: None,
+                        south: None,
+                        west: None,
+                    },
+                    kind: RoomKind::Plain,
+                    item: None,
+                }
             };
             rooms.push(r);
         }
+        let first = mask.rooms().next().unwrap_or_default();
+        let last = mask.rooms().last().unwrap_or_default();
+        let goal = if wrap {
+            BoundedIx2::new(N_ROWS / 2, N_COLS / 2)
+                .filter(|ix| mask.is_room(*ix))
+                .unwrap_or(last)
+        } else {
+            last
+        };
         Self {
             rooms: V2::new(rooms).unwrap(),
-            current_ix: BoundedIx2::new(0, 0).unwrap(),
-            goal: BoundedIx2::<N_ROWS, N_COLS>::max(),
+            current_ix: first,
+            goals: Goals::single(goal),
+            wrap,
+            mask,
+            score: 0,
+            collected: 0,
+            items_required: 0,
+            last_pickup: None,
+            trail: Vec::new(),
+            history: Vec::new(),
+            undos_left: None,
+            undo_cost: 0,
+        }
+    }
+    pub fn is_room(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> bool {
+        self.mask.is_room(ix)
+    }
+    /// indices of every room, skipping empty space
+    pub fn indices(&self) -> impl Iterator<Item = BoundedIx2<N_ROWS, N_COLS>> + '_ {
+        self.mask.rooms()
+    }
+    /// the room next to `ix` in direction `dir`, wrapping around the edges if the maze wraps
+    pub fn neighbor(
+        &self,
+        ix: BoundedIx2<N_ROWS, N_COLS>,
+        dir: Direction,
+    ) -> Option<BoundedIx2<N_ROWS, N_COLS>> {
+        neighbor(ix, dir, self.wrap, &self.mask)
+    }
+    /// the cell `d_rows` down and `d_cols` across from `ix`, wrapping around the edges if the
+    /// maze wraps
+    pub fn offset(
+        &self,
+        ix: BoundedIx2<N_ROWS, N_COLS>,
+        d_rows: isize,
+        d_cols: isize,
+    ) -> Option<BoundedIx2<N_ROWS, N_COLS>> {
+        let row = ix.y() as isize + d_rows;
+        let col = ix.x() as isize + d_cols;
+        if self.wrap {
+            BoundedIx2::new(
+                row.rem_euclid(N_ROWS as isize) as usize,
+                col.rem_euclid(N_COLS as isize) as usize,
+            )
+        } else if row < 0 || col < 0 {
+            None
+        } else {
+            BoundedIx2::new(row as usize, col as usize)
         }
     }
+    /// doors of the room at `ix` that lead around to the opposite edge
+    pub fn wrap_doors(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> Vec<Direction> {
+        if !self.wrap {
+            return Vec::new();
+        }
+        let mut dirs = Vec::with_capacity(2);
+        if ix.north().is_none() {
+            dirs.push(Direction::North);
+        }
+        if ix.east().is_none() {
+            dirs.push(Direction::East);
+        }
+        if ix.south().is_none() {
+            dirs.push(Direction::South);
+        }
+        if ix.west().is_none() {
+            dirs.push(Direction::West);
+        }
+        dirs
+    }
     pub fn open_north(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
         self.rooms[ix].doors.open_north();
-        if let Some(r) = self.rooms.get_mut(ix.north()) {
+        if let Some(r) = self.rooms.get_mut(self.neighbor(ix, Direction::North)) {
             r.doors.open_south();
         }
     }
     pub fn open_east(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
         self.rooms[ix].doors.open_east();
-        if let Some(r) = self.rooms.get_mut(ix.east()) {
+        if let Some(r) = self.rooms.get_mut(self.neighbor(ix, Direction::East)) {
             r.doors.open_west();
         }
     }
     pub fn open_south(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
         self.rooms[ix].doors.open_south();
-        if let Some(r) = self.rooms.get_mut(ix.south()) {
+        if let Some(r) = self.rooms.get_mut(self.neighbor(ix, Direction::South)) {
             r.doors.open_north();
         }
     }
     pub fn open_west(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
         self.rooms[ix].doors.open_west();
-        if let Some(r) = self.rooms.get_mut(ix.west()) {
+        if let Some(r) = self.rooms.get_mut(self.neighbor(ix, Direction::West)) {
             r.doors.open_east();
         }
     }
+    pub fn open_dir(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>, dir: Direction) {
+        match dir {
+            Direction::North => self.open_north(ix),
+            Direction::East => self.open_east(ix),
+            Direction::South => self.open_south(ix),
+            Direction::West => self.open_west(ix),
+        }
+    }
     pub fn close_north(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
         self.rooms[ix].doors.close_north();
-        if let Some(r) = self.rooms.get_mut(ix.north()) {
+        if let Some(r) = self.rooms.get_mut(self.neighbor(ix, Direction::North)) {
             r.doors.close_south();
         }
     }
     pub fn close_east(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
         self.rooms[ix].doors.close_east();
-        if let Some(r) = self.rooms.get_mut(ix.east()) {
+        if let Some(r) = self.rooms.get_mut(self.neighbor(ix, Direction::East)) {
             r.doors.close_west();
         }
     }
     pub fn close_south(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
         self.rooms[ix].doors.close_south();
-        if let Some(r) = self.rooms.get_mut(ix.south()) {
+        if let Some(r) = self.rooms.get_mut(self.neighbor(ix, Direction::South)) {
             r.doors.close_north();
         }
     }
     pub fn close_west(&mut self, ix: BoundedIx2<N_ROWS, N_COLS>) {
         self.rooms[ix].doors.close_west();
-        if let Some(r) = self.rooms.get_mut(ix.west()) {
+        if let Some(r) = self.rooms.get_mut(self.neighbor(ix, Direction::West)) {
             r.doors.close_east();
         }
     }
     pub fn move_north(&mut self) -> bool {
-        match self.rooms[self.current_ix].doors.north {
+        self.move_dir(Direction::North)
+    }
+    pub fn move_south(&mut self) -> bool {
+        self.move_dir(Direction::South)
+    }
+    pub fn move_east(&mut self) -> bool {
+        self.move_dir(Direction::East)
+    }
+    pub fn move_west(&mut self) -> bool {
+        self.move_dir(Direction::West)
+    }
+    pub fn move_dir(&mut self, dir: Direction) -> bool {
+        match self.rooms[self.current_ix].doors.get(dir) {
             Some(DoorState::Open) => {
-                self.current_ix = self.current_ix.north().unwrap();
+                let mut next = self.neighbor(self.current_ix, dir).unwrap();
+                while self.rooms[next].is_crossing() {
+                    next = self.neighbor(next, dir).unwrap();
+                }
+                if self.trail.is_empty() {
+                    self.trail.push(self.current_ix);
+                }
+                self.trail.push(next);
+                self.history.push(self.current_ix);
+                self.current_ix = next;
+                self.collect();
+                if self.collected >= self.items_required {
+                    self.goals.visit(next);
+                }
                 true
             }
             _ => false,
         }
     }
-    pub fn move_south(&mut self) -> bool {
-        match self.rooms[self.current_ix].doors.south {
-            Some(DoorState::Open) => {
-                self.current_ix = self.current_ix.south().unwrap();
-                true
+    /// go back to the room the player was in before their last move; what they picked up stays
+    /// picked up, and the step back goes on the trail like any other
+    pub fn undo(&mut self) -> bool {
+        if self.undos_left == Some(0) {
+            return false;
+        }
+        let Some(prev) = self.history.pop() else {
+            return false;
+        };
+        if let Some(left) = &mut self.undos_left {
+            *left -= 1;
+        }
+        self.score = self.score.saturating_sub(self.undo_cost);
+        self.trail.push(prev);
+        self.current_ix = prev;
+        self.last_pickup = None;
+        if self.collected >= self.items_required {
+            self.goals.visit(prev);
+        }
+        true
+    }
+    /// pick up whatever's in the current room
+    fn collect(&mut self) {
+        self.last_pickup = self.rooms[self.current_ix].item.take();
+        match self.last_pickup {
+            None | Some(Item::Fuel) => (),
+            Some(item) => {
+                self.score += item.value();
+                self.collected += 1;
             }
-            _ => false,
         }
     }
-    pub fn move_east(&mut self) -> bool {
-        match self.rooms[self.current_ix].doors.east {
-            Some(DoorState::Open) => {
-                self.current_ix = self.current_ix.east().unwrap();
-                true
+    /// crossings that lead straight on from `ix` through open doors, i.e. the ones the player
+    /// has just passed through or is about to
+    pub fn crossings_from(
+        &self,
+        ix: BoundedIx2<N_ROWS, N_COLS>,
+    ) -> Vec<BoundedIx2<N_ROWS, N_COLS>> {
+        let mut crossings = Vec::new();
+        for dir in DirectionsIter::new() {
+            let mut curr = ix;
+            while self.rooms[curr].doors.get(dir) == Some(DoorState::Open) {
+                match self.neighbor(curr, dir) {
+                    Some(next) if self.rooms[next].is_crossing() && next != ix => {
+                        crossings.push(next);
+                        curr = next;
+                    }
+                    _ => break,
+                }
             }
-            _ => false,
         }
+        crossings
     }
-    pub fn move_west(&mut self) -> bool {
-        match self.rooms[self.current_ix].doors.west {
-            Some(DoorState::Open) => {
-                self.current_ix = self.current_ix.west().unwrap();
-                true
+    /// rooms that can be seen from `ix` looking straight down each corridor, along with which way
+    /// and how many rooms away they are; the view stops at the first closed door or after `max`
+    /// rooms
+    pub fn sight_lines(
+        &self,
+        ix: BoundedIx2<N_ROWS, N_COLS>,
+        max: usize,
+    ) -> Vec<(Direction, usize, BoundedIx2<N_ROWS, N_COLS>)> {
+        let mut seen = Vec::new();
+        for dir in DirectionsIter::new() {
+            let mut curr = ix;
+            for dist in 1..=max {
+                if self.rooms[curr].doors.get(dir) != Some(DoorState::Open) {
+                    break;
+                }
+                match self.neighbor(curr, dir) {
+                    Some(next) if next != ix => {
+                        seen.push((dir, dist, next));
+                        curr = next;
+                    }
+                    _ => break,
+                }
             }
-            _ => false,
         }
+        seen
+    }
+    /// rooms the player can get to from `ix` in a single move
+    pub fn moves_from(
+        &self,
+        ix: BoundedIx2<N_ROWS, N_COLS>,
+    ) -> impl Iterator<Item = (Direction, BoundedIx2<N_ROWS, N_COLS>)> + '_ {
+        self.rooms[ix]
+            .all_doors()
+            .filter(|(_, st)| *st == DoorState::Open)
+            .filter_map(move |(dir, _)| {
+                let mut next = self.neighbor(ix, dir)?;
+                while self.rooms[next].is_crossing() {
+                    next = self.neighbor(next, dir)?;
+                }
+                Some((dir, next))
+            })
+    }
+    /// the fewest moves it takes to get from `ix` to every room, or `None` for rooms that can't
+    /// be reached (or stopped in, like crossings)
+    pub fn distances_from(
+        &self,
+        ix: BoundedIx2<N_ROWS, N_COLS>,
+    ) -> V2<Option<usize>, N_ROWS, N_COLS> {
+        let mut dists: V2<Option<usize>, N_ROWS, N_COLS> = V2::default();
+        dists[ix] = Some(0);
+        let mut todo = VecDeque::from([(ix, 0)]);
+        while let Some((curr, dist)) = todo.pop_front() {
+            for (_, next) in self.moves_from(curr) {
+                if dists[next].is_none() {
+                    dists[next] = Some(dist + 1);
+                    todo.push_back((next, dist + 1));
+                }
+            }
+        }
+        dists
+    }
+    /// the rooms on a shortest way from `from` to `to`, both included, or `None` if there isn't
+    /// one
+    pub fn shortest_path(
+        &self,
+        from: BoundedIx2<N_ROWS, N_COLS>,
+        to: BoundedIx2<N_ROWS, N_COLS>,
+    ) -> Option<Vec<BoundedIx2<N_ROWS, N_COLS>>> {
+        let dists = self.distances_from(to);
+        let mut path = vec![from];
+        let mut ix = from;
+        while ix != to {
+            let (_, next) = self
+                .moves_from(ix)
+                .filter(|(_, next)| dists[*next].is_some())
+                .min_by_key(|(_, next)| dists[*next])?;
+            path.push(next);
+            ix = next;
+        }
+        Some(path)
+    }
+    /// a way to win from where the player is: first to the nearest item until enough have
+    /// been picked up, then to the nearest goal if any will do, or through every goal not yet
+    /// reached in order otherwise. each leg is as short as it can be, though the whole way
+    /// might not be. `None` if there's no way to win
+    pub fn solution(&self) -> Option<Vec<BoundedIx2<N_ROWS, N_COLS>>> {
+        let counts = |ix: BoundedIx2<N_ROWS, N_COLS>| matches!(self.rooms[ix].item, Some(item) if item != Item::Fuel);
+        let mut path = vec![self.current_ix];
+        let mut needed = self.items_required.saturating_sub(self.collected);
+        while needed > 0 {
+            let from = *path.last().unwrap();
+            let dists = self.distances_from(from);
+            let nearest = self
+                .indices()
+                .filter(|ix| counts(*ix) && !path.contains(ix) && dists[*ix].is_some())
+                .min_by_key(|ix| dists[*ix])?;
+            for ix in &self.shortest_path(from, nearest)?[1..] {
+                if counts(*ix) && !path.contains(ix) {
+                    needed -= 1;
+                }
+                path.push(*ix);
+            }
+        }
+        let left: Vec<_> = self
+            .goals
+            .iter()
+            .filter(|goal| !self.goals.is_reached(*goal))
+            .collect();
+        if self.goals.condition == WinCondition::Any {
+            if self.goals.is_done() {
+                return Some(path);
+            }
+            let from = *path.last().unwrap();
+            let dists = self.distances_from(from);
+            let nearest = left
+                .into_iter()
+                .filter(|goal| dists[*goal].is_some())
+                .min_by_key(|goal| dists[*goal])?;
+            path.extend(&self.shortest_path(from, nearest)?[1..]);
+            return Some(path);
+        }
+        for goal in left {
+            let leg = self.shortest_path(*path.last().unwrap(), goal)?;
+            path.extend(&leg[1..]);
+        }
+        Some(path)
+    }
+    /// an error if this isn't a maze the game can be played in: every door has to lead to a
+    /// room with the same door back, crossings have to be open on all four sides, and the
+    /// player and the goals have to be in rooms. loading checks this, since a maze from a file
+    /// can be anything
+    pub fn check(&self) -> Result<()> {
+        for ix in self.indices() {
+            let room = &self.rooms[ix];
+            for dir in DirectionsIter::new() {
+                match (room.doors.get(dir), self.neighbor(ix, dir)) {
+                    (None, None) => (),
+                    (Some(_), None) => bail!("room {ix} has a door {dir:?} that leads nowhere"),
+                    (None, Some(_)) => bail!("room {ix} has no door {dir:?}"),
+                    (Some(st), Some(next)) => {
+                        if self.rooms[next].doors.get(dir.opposite()) != Some(st) {
+                            bail!("door {dir:?} from {ix} doesn't match the one back from {next}");
+                        }
+                    }
+                }
+            }
+            if room.is_crossing()
+                && (room.all_doors().count() != 4
+                    || room.all_doors().any(|(_, st)| st != DoorState::Open))
+            {
+                bail!("crossing at {ix} has to be open on all four sides");
+            }
+        }
+        if !self.is_room(self.current_ix) {
+            bail!("current room {} isn't in the maze", self.current_ix);
+        }
+        if let Some(goal) = self.goals.iter().find(|goal| !self.is_room(*goal)) {
+            bail!("goal {goal} isn't in the maze");
+        }
+        Ok(())
+    }
+    /// how many times the player has stopped in `ix`
+    pub fn visits(&self, ix: BoundedIx2<N_ROWS, N_COLS>) -> usize {
+        self.trail.iter().filter(|t| **t == ix).count()
+    }
+    pub fn max_visits(&self) -> usize {
+        self.indices().map(|ix| self.visits(ix)).max().unwrap_or(0)
     }
     pub fn is_done(&self) -> bool {
-        self.current_ix == self.goal
+        self.goals.is_done()
+    }
+    /// what to show in the HUD
+    pub fn status(&self) -> Vec<String> {
+        let mut status = vec![self.goals.progress(), format!("score {}", self.score)];
+        if self.items_required > 0 {
+            status.push(format!(
+                "items {}/{} needed",
+                self.collected.min(self.items_required),
+                self.items_required
+            ));
+        }
+        if let Some(left) = self.undos_left {
+            status.push(format!("undos {left}"));
+        }
+        status
     }
 }
 
@@ -262,6 +718,162 @@ impl<const N_ROWS: usize, const N_COLS: usize> Default for Maze<N_ROWS, N_COLS>
     }
 }
 
+/// order a room's doors are written in when it's saved
+const SAVE_ORDER: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+impl<const N_ROWS: usize, const N_COLS: usize> Save for Maze<N_ROWS, N_COLS> {
+    /// one line per row with a word for each room: `.` for empty space, otherwise its north,
+    /// east, south and west doors (`o`pen, `x` closed or `-` none), then `|` or `=` for a
+    /// crossing over a north-south or east-west corridor, then what's lying in it
+    fn save(&self, out: &mut Writer) {
+        out.list("size", [N_ROWS, N_COLS]);
+        out.field("wrap", self.wrap);
+        for row in 0..N_ROWS {
+            let words = (0..N_COLS).map(|col| {
+                let ix = BoundedIx2::new(row, col).unwrap();
+                if !self.is_room(ix) {
+                    return ".".to_string();
+                }
+                let room = &self.rooms[ix];
+                let mut word: String = SAVE_ORDER
+                    .into_iter()
+                    .map(|dir| room.doors.get(dir).map_or("-", |st| st.token()))
+                    .collect();
+                match room.kind {
+                    RoomKind::Plain => (),
+                    RoomKind::Crossing {
+                        over: Axis::NorthSouth,
+                    } => word.push('|'),
+                    RoomKind::Crossing {
+                        over: Axis::EastWest,
+                    } => word.push('='),
+                }
+                if let Some(item) = room.item {
+                    word.push_str(item.token());
+                }
+                word
+            });
+            out.list("row", words);
+        }
+        out.field("current", save::ix(self.current_ix));
+        self.goals.save(out);
+        out.field("score", self.score);
+        out.field("collected", self.collected);
+        out.field("items_required", self.items_required);
+        out.field(
+            "last_pickup",
+            self.last_pickup.map_or("-", |item| item.token()),
+        );
+        out.list("trail", self.trail.iter().map(|ix| save::ix(*ix)));
+        out.list("history", self.history.iter().map(|ix| save::ix(*ix)));
+        out.field("undos_left", save::optional(self.undos_left));
+        out.field("undo_cost", self.undo_cost);
+    }
+    fn load(input: &mut Reader) -> Result<Self> {
+        input.size(N_ROWS, N_COLS)?;
+        let wrap = input.parse("wrap")?;
+        let mut rows = Vec::with_capacity(N_ROWS);
+        for _ in 0..N_ROWS {
+            let row = input.list("row", |word| Some(word.to_string()))?;
+            if row.len() != N_COLS {
+                bail!("expected {N_COLS} rooms in a row, got {}", row.len());
+            }
+            rows.push(row);
+        }
+        let mask = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|word| if word == "." { '.' } else { '#' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .parse()?;
+        let mut maze = Self::with_mask(mask, wrap);
+        for ix in maze.indices().collect::<Vec<_>>() {
+            let word = &rows[ix.y()][ix.x()];
+            let mut chars = word.chars();
+            let room = &mut maze.rooms[ix];
+            for dir in SAVE_ORDER {
+                let door = match chars.next() {
+                    Some('-') => None,
+                    Some(c) => Some(
+                        DoorState::from_token(&c.to_string())
+                            .ok_or_else(|| eyre!("bad room {word:?} at {ix}"))?,
+                    ),
+                    None => bail!("bad room {word:?} at {ix}"),
+                };
+                *room.doors.get_mut(dir) = door;
+            }
+            let mut rest = chars.as_str();
+            if let Some(r) = rest.strip_prefix('|') {
+                room.kind = RoomKind::Crossing {
+                    over: Axis::NorthSouth,
+                };
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('=') {
+                room.kind = RoomKind::Crossing {
+                    over: Axis::EastWest,
+                };
+                rest = r;
+            }
+            room.item = match rest {
+                "" => None,
+                token => Some(
+                    Item::from_token(token).ok_or_else(|| eyre!("bad room {word:?} at {ix}"))?,
+                ),
+            };
+        }
+        let ix = |s: &str| save::parse_ix(s).map(|(ix, _)| ix);
+        maze.current_ix = ix(input.field("current")?).ok_or_else(|| eyre!("bad current room"))?;
+        maze.goals = Goals::load(input)?;
+        maze.score = input.parse("score")?;
+        maze.collected = input.parse("collected")?;
+        maze.items_required = input.parse("items_required")?;
+        maze.last_pickup = match input.field("last_pickup")? {
+            "-" => None,
+            token => Some(Item::from_token(token).ok_or_else(|| eyre!("bad last pickup"))?),
+        };
+        maze.trail = input.list("trail", ix)?;
+        maze.history = input.list("history", ix)?;
+        maze.undos_left = input.optional("undos_left")?;
+        maze.undo_cost = input.parse("undo_cost")?;
+        maze.check()?;
+        Ok(maze)
+    }
+}
+
+fn neighbor<const N_ROWS: usize, const N_COLS: usize>(
+    ix: BoundedIx2<N_ROWS, N_COLS>,
+    dir: Direction,
+    wrap: bool,
+    mask: &Mask<N_ROWS, N_COLS>,
+) -> Option<BoundedIx2<N_ROWS, N_COLS>> {
+    let next = match dir {
+        Direction::North => ix.north(),
+        Direction::East => ix.east(),
+        Direction::South => ix.south(),
+        Direction::West => ix.west(),
+    };
+    if next.is_some() || !wrap {
+        return next.filter(|n| mask.is_room(*n));
+    }
+    let wrapped = match dir {
+        Direction::North => BoundedIx2::new(N_ROWS - 1, ix.x()),
+        Direction::East => BoundedIx2::new(ix.y(), 0),
+        Direction::South => BoundedIx2::new(0, ix.x()),
+        Direction::West => BoundedIx2::new(ix.y(), N_COLS - 1),
+    };
+    // a single row or column would otherwise get a door leading back into the same room
+    wrapped.filter(|w| *w != ix && mask.is_room(*w))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -390,4 +1002,149 @@ mod test {
         let ix2 = BoundedIx2::<3, 3>::new(0, 0).unwrap();
         assert_eq!(Some(DoorState::Open), m.rooms[ix2].doors.east, "neighbor");
     }
+    #[test]
+    fn test_open_east_toroidal() {
+        let mut m = Maze::<3, 3>::new_toroidal();
+        let ix = BoundedIx2::<3, 3>::new(1, 2).unwrap();
+        m.open_east(ix);
+        assert_eq!(
+            Some(DoorState::Open),
+            m.rooms[ix].doors.east,
+            "original room"
+        );
+        let ix2 = BoundedIx2::<3, 3>::new(1, 0).unwrap();
+        assert_eq!(Some(DoorState::Open), m.rooms[ix2].doors.west, "neighbor");
+        m.current_ix = ix;
+        assert!(m.move_east());
+        assert_eq!(ix2, m.current_ix);
+    }
+    #[test]
+    fn test_move_through_crossing() {
+        let mut m = Maze::<3, 3>::new();
+        let mid = BoundedIx2::<3, 3>::new(1, 1).unwrap();
+        m.open_north(mid);
+        m.open_south(mid);
+        m.open_east(mid);
+        m.open_west(mid);
+        m.rooms[mid].kind = RoomKind::Crossing {
+            over: Axis::NorthSouth,
+        };
+        m.current_ix = BoundedIx2::new(1, 0).unwrap();
+        assert!(m.move_east());
+        assert_eq!(BoundedIx2::new(1, 2).unwrap(), m.current_ix);
+        assert_eq!(vec![mid], m.crossings_from(m.current_ix));
+        assert_eq!(0, m.visits(mid), "passed through, not stopped in");
+        assert_eq!(2, m.trail.len());
+    }
+    #[test]
+    fn test_undo() {
+        let mut m = Maze::<1, 3>::new();
+        m.open_east(BoundedIx2::new(0, 0).unwrap());
+        m.open_east(BoundedIx2::new(0, 1).unwrap());
+        m.rooms[BoundedIx2::new(0, 1).unwrap()].item = Some(Item::Gem);
+        m.undos_left = Some(1);
+        m.undo_cost = 2;
+        assert!(!m.undo(), "nothing to undo");
+        assert!(m.move_east() && m.move_east());
+        assert!(m.undo());
+        assert_eq!(BoundedIx2::new(0, 1).unwrap(), m.current_ix);
+        assert_eq!(3, m.score);
+        assert!(!m.undo(), "out of undos");
+    }
+    #[test]
+    fn test_sight_lines() {
+        let mut m = Maze::<1, 4>::new();
+        m.open_east(BoundedIx2::new(0, 0).unwrap());
+        m.open_east(BoundedIx2::new(0, 1).unwrap());
+        let seen = m.sight_lines(BoundedIx2::new(0, 0).unwrap(), 5);
+        assert_eq!(
+            vec![
+                (Direction::East, 1, BoundedIx2::new(0, 1).unwrap()),
+                (Direction::East, 2, BoundedIx2::new(0, 2).unwrap()),
+            ],
+            seen,
+            "stops at the closed door"
+        );
+    }
+    #[test]
+    fn test_items_required() {
+        let mut m = Maze::<1, 3>::new();
+        m.open_east(BoundedIx2::new(0, 0).unwrap());
+        m.open_east(BoundedIx2::new(0, 1).unwrap());
+        m.items_required = 1;
+        let mut m2 = m.clone();
+        m.rooms[BoundedIx2::new(0, 1).unwrap()].item = Some(Item::Gem);
+        assert!(m.move_east() && m.move_east());
+        assert!(m.is_done());
+        assert_eq!(5, m.score);
+        assert_eq!(None, m.rooms[BoundedIx2::new(0, 1).unwrap()].item);
+        assert!(m2.move_east() && m2.move_east());
+        assert!(!m2.is_done(), "nothing picked up");
+    }
+    #[test]
+    fn test_load() {
+        let mut m = Maze::<3, 3>::new();
+        let mid = BoundedIx2::<3, 3>::new(1, 1).unwrap();
+        for dir in DirectionsIter::new() {
+            m.open_dir(mid, dir);
+        }
+        m.rooms[mid].kind = RoomKind::Crossing {
+            over: Axis::NorthSouth,
+        };
+        let load = |m: &Maze<3, 3>| {
+            let mut out = Writer::default();
+            m.save(&mut out);
+            Maze::<3, 3>::load(&mut Reader::new(&out.finish()))
+        };
+        assert!(load(&m).is_ok());
+        let mut edge = m.clone();
+        edge.rooms[BoundedIx2::new(0, 1).unwrap()].kind = RoomKind::Crossing {
+            over: Axis::EastWest,
+        };
+        assert!(load(&edge).is_err(), "crossing on the edge");
+        let mut one_way = m.clone();
+        one_way.rooms[BoundedIx2::new(0, 0).unwrap()].doors.east = Some(DoorState::Open);
+        assert!(
+            load(&one_way).is_err(),
+            "door that doesn't match the one back"
+        );
+        let mut masked: Maze<3, 3> = Maze::with_mask("##.\n###\n###".parse().unwrap(), false);
+        masked.current_ix = BoundedIx2::new(0, 2).unwrap();
+        assert!(load(&masked).is_err(), "current room masked out");
+    }
+    #[test]
+    fn test_solution() {
+        let mut m = Maze::<1, 4>::new();
+        for col in 0..3 {
+            m.open_east(BoundedIx2::new(0, col).unwrap());
+        }
+        let ix = |col| BoundedIx2::new(0, col).unwrap();
+        m.current_ix = ix(1);
+        assert_eq!(Some(vec![ix(1), ix(2), ix(3)]), m.solution());
+        m.rooms[ix(0)].item = Some(Item::Gem);
+        m.items_required = 1;
+        let path = m.solution().unwrap();
+        assert_eq!(vec![ix(1), ix(0), ix(1), ix(2), ix(3)], path);
+        for pair in path.windows(2) {
+            m.move_dir(if pair[1] > pair[0] {
+                Direction::East
+            } else {
+                Direction::West
+            });
+        }
+        assert!(m.is_done(), "picked up the gem on the way");
+        let mut m = Maze::<1, 4>::new();
+        for col in 0..3 {
+            m.open_east(BoundedIx2::new(0, col).unwrap());
+        }
+        m.goals.insert_checkpoint(ix(2));
+        m.goals.condition = WinCondition::Ordered;
+        m.goals.visit(ix(2));
+        m.current_ix = ix(1);
+        assert_eq!(
+            Some(vec![ix(1), ix(2), ix(3)]),
+            m.solution(),
+            "the checkpoint's already reached"
+        );
+    }
 }
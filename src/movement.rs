@@ -1,4 +1,4 @@
-use crate::maze::Maze;
+use crate::{Direction, maze::Maze};
 use crossterm::event::{Event, KeyCode, KeyEvent};
 use rand::{distr::StandardUniform, prelude::*};
 
@@ -27,6 +27,18 @@ pub enum MazeEvent {
     MoveW,
     Enter,
     Quit,
+    /// step back along the way the player came
+    Undo,
+    /// start the same maze again
+    Restart,
+    /// give up on this maze and play a new one
+    NewMaze,
+    /// save the game and stop, to carry on later
+    Save,
+    /// chalk a mark on the floor
+    Mark,
+    /// chalk a mark by a door
+    MarkDoor(Direction),
     OtherKey(KeyCode),
     Other(Event),
 }
@@ -96,6 +108,46 @@ impl From<Event> for MazeEvent {
                 code: KeyCode::Char(' '),
                 ..
             }) => MazeEvent::Enter,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('u'),
+                ..
+            })
+            | Event::Key(KeyEvent {
+                code: KeyCode::Backspace,
+                ..
+            }) => MazeEvent::Undo,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('r'),
+                ..
+            }) => MazeEvent::Restart,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('n'),
+                ..
+            }) => MazeEvent::NewMaze,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('x'),
+                ..
+            }) => MazeEvent::Save,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('c'),
+                ..
+            }) => MazeEvent::Mark,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('W' | 'K'),
+                ..
+            }) => MazeEvent::MarkDoor(Direction::North),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('S' | 'J'),
+                ..
+            }) => MazeEvent::MarkDoor(Direction::South),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('D' | 'L'),
+                ..
+            }) => MazeEvent::MarkDoor(Direction::East),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('A' | 'H'),
+                ..
+            }) => MazeEvent::MarkDoor(Direction::West),
             Event::Key(KeyEvent { code: kc, .. }) => MazeEvent::OtherKey(kc),
             other => MazeEvent::Other(other),
         }
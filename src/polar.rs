@@ -0,0 +1,376 @@
+use crate::{
+    maze::DoorState,
+    save::{Reader, Save, Token, Writer},
+};
+use color_eyre::{
+    Result,
+    eyre::{bail, eyre},
+};
+use std::f64::consts::TAU;
+
+/// number of rooms in the first ring around the central room
+pub const INNER_RING_ROOMS: usize = 6;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Default)]
+pub struct PolarIx {
+    /// distance from the centre, 0 being the central room
+    pub ring: usize,
+    /// position within the ring, counting counter-clockwise from due east
+    pub cell: usize,
+}
+
+impl PolarIx {
+    pub fn new(ring: usize, cell: usize) -> Self {
+        Self { ring, cell }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PolarDirection {
+    Inward,
+    /// outer rings can be subdivided, so a room can have several outward doors
+    Outward(usize),
+    Clockwise,
+    CounterClockwise,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PolarDoors {
+    pub inward: Option<DoorState>,
+    pub outward: Vec<DoorState>,
+    pub clockwise: Option<DoorState>,
+    pub counter_clockwise: Option<DoorState>,
+}
+
+impl PolarDoors {
+    pub fn get(&self, dir: PolarDirection) -> Option<DoorState> {
+        match dir {
+            PolarDirection::Inward => self.inward,
+            PolarDirection::Outward(n) => self.outward.get(n).copied(),
+            PolarDirection::Clockwise => self.clockwise,
+            PolarDirection::CounterClockwise => self.counter_clockwise,
+        }
+    }
+    fn set(&mut self, dir: PolarDirection, st: DoorState) {
+        let door = match dir {
+            PolarDirection::Inward => &mut self.inward,
+            PolarDirection::Outward(n) => {
+                if let Some(d) = self.outward.get_mut(n) {
+                    *d = st;
+                }
+                return;
+            }
+            PolarDirection::Clockwise => &mut self.clockwise,
+            PolarDirection::CounterClockwise => &mut self.counter_clockwise,
+        };
+        if door.is_some() {
+            *door = Some(st)
+        }
+    }
+    pub fn all_doors(&self) -> impl Iterator<Item = (PolarDirection, DoorState)> + '_ {
+        self.inward
+            .map(|st| (PolarDirection::Inward, st))
+            .into_iter()
+            .chain(
+                self.outward
+                    .iter()
+                    .enumerate()
+                    .map(|(n, st)| (PolarDirection::Outward(n), *st)),
+            )
+            .chain(self.clockwise.map(|st| (PolarDirection::Clockwise, st)))
+            .chain(
+                self.counter_clockwise
+                    .map(|st| (PolarDirection::CounterClockwise, st)),
+            )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PolarRoom {
+    pub doors: PolarDoors,
+}
+
+/// a circular maze: a central room surrounded by `n_rings - 1` rings, with each ring split
+/// into roughly square rooms, so rings further out have more rooms
+#[derive(Debug, Clone)]
+pub struct PolarMaze {
+    pub rings: Vec<Vec<PolarRoom>>,
+    pub current_ix: PolarIx,
+    pub goal: PolarIx,
+    /// every room the player has been in, in order, once they've started moving
+    pub trail: Vec<PolarIx>,
+}
+
+impl PolarMaze {
+    pub fn new(n_rings: usize) -> Self {
+        let n_rings = n_rings.max(2);
+        let mut sizes: Vec<usize> = Vec::with_capacity(n_rings);
+        sizes.push(1);
+        sizes.push(INNER_RING_ROOMS);
+        for ring in 2..n_rings {
+            let prev = sizes[ring - 1];
+            let ratio = (TAU * ring as f64 / prev as f64).round().max(1.0) as usize;
+            sizes.push(prev * ratio);
+        }
+        let mut rings: Vec<Vec<PolarRoom>> = Vec::with_capacity(n_rings);
+        for (ring, size) in sizes.iter().enumerate() {
+            let n_outward = sizes.get(ring + 1).map(|next| next / size).unwrap_or(0);
+            let mut rooms = Vec::with_capacity(*size);
+            for _ in 0..*size {
+                rooms.push(PolarRoom {
+                    doors: PolarDoors {
+                        inward: (ring > 0).then_some(DoorState::Closed),
+                        outward: vec![DoorState::Closed; n_outward],
+                        clockwise: (ring > 0).then_some(DoorState::Closed),
+                        counter_clockwise: (ring > 0).then_some(DoorState::Closed),
+                    },
+                });
+            }
+            rings.push(rooms);
+        }
+        Self {
+            rings,
+            current_ix: PolarIx::new(n_rings - 1, 0),
+            goal: PolarIx::new(0, 0),
+            trail: Vec::new(),
+        }
+    }
+    pub fn n_rings(&self) -> usize {
+        self.rings.len()
+    }
+    pub fn ring_len(&self, ring: usize) -> usize {
+        self.rings[ring].len()
+    }
+    pub fn room(&self, ix: PolarIx) -> &PolarRoom {
+        &self.rings[ix.ring][ix.cell]
+    }
+    fn room_mut(&mut self, ix: PolarIx) -> &mut PolarRoom {
+        &mut self.rings[ix.ring][ix.cell]
+    }
+    pub fn indices(&self) -> impl Iterator<Item = PolarIx> + '_ {
+        self.rings
+            .iter()
+            .enumerate()
+            .flat_map(|(ring, rooms)| (0..rooms.len()).map(move |cell| PolarIx { ring, cell }))
+    }
+    pub fn neighbor(&self, ix: PolarIx, dir: PolarDirection) -> Option<PolarIx> {
+        match dir {
+            PolarDirection::Inward => {
+                if ix.ring == 0 {
+                    None
+                } else {
+                    let ratio = self.ring_len(ix.ring) / self.ring_len(ix.ring - 1);
+                    Some(PolarIx::new(ix.ring - 1, ix.cell / ratio))
+                }
+            }
+            PolarDirection::Outward(n) => {
+                let n_outward = self.room(ix).doors.outward.len();
+                if n < n_outward {
+                    Some(PolarIx::new(ix.ring + 1, ix.cell * n_outward + n))
+                } else {
+                    None
+                }
+            }
+            PolarDirection::Clockwise => {
+                if ix.ring == 0 {
+                    None
+                } else {
+                    let len = self.ring_len(ix.ring);
+                    Some(PolarIx::new(ix.ring, (ix.cell + len - 1) % len))
+                }
+            }
+            PolarDirection::CounterClockwise => {
+                if ix.ring == 0 {
+                    None
+                } else {
+                    let len = self.ring_len(ix.ring);
+                    Some(PolarIx::new(ix.ring, (ix.cell + 1) % len))
+                }
+            }
+        }
+    }
+    /// the direction that leads back from `dir`'s neighbor to `ix`
+    pub fn reverse(&self, ix: PolarIx, dir: PolarDirection) -> PolarDirection {
+        match dir {
+            PolarDirection::Inward => {
+                let ratio = self.ring_len(ix.ring) / self.ring_len(ix.ring - 1);
+                PolarDirection::Outward(ix.cell % ratio)
+            }
+            PolarDirection::Outward(_) => PolarDirection::Inward,
+            PolarDirection::Clockwise => PolarDirection::CounterClockwise,
+            PolarDirection::CounterClockwise => PolarDirection::Clockwise,
+        }
+    }
+    pub fn neighbors(&self, ix: PolarIx) -> impl Iterator<Item = (PolarDirection, PolarIx)> + '_ {
+        self.room(ix)
+            .doors
+            .all_doors()
+            .filter_map(move |(dir, _)| self.neighbor(ix, dir).map(|n| (dir, n)))
+    }
+    pub fn open(&mut self, ix: PolarIx, dir: PolarDirection) {
+        self.set_door(ix, dir, DoorState::Open)
+    }
+    pub fn close(&mut self, ix: PolarIx, dir: PolarDirection) {
+        self.set_door(ix, dir, DoorState::Closed)
+    }
+    fn set_door(&mut self, ix: PolarIx, dir: PolarDirection, st: DoorState) {
+        if let Some(n) = self.neighbor(ix, dir) {
+            let rev = self.reverse(ix, dir);
+            self.room_mut(ix).doors.set(dir, st);
+            self.room_mut(n).doors.set(rev, st);
+        }
+    }
+    pub fn move_dir(&mut self, dir: PolarDirection) -> bool {
+        match self.room(self.current_ix).doors.get(dir) {
+            Some(DoorState::Open) => {
+                if self.trail.is_empty() {
+                    self.trail.push(self.current_ix);
+                }
+                self.current_ix = self.neighbor(self.current_ix, dir).unwrap();
+                self.trail.push(self.current_ix);
+                true
+            }
+            _ => false,
+        }
+    }
+    /// move through whichever open door lies closest to the on-screen direction `(dx, dy)`,
+    /// which keeps the usual controls working whichever side of the circle the player is on
+    pub fn move_toward(&mut self, dx: f64, dy: f64) -> bool {
+        let (cx, cy) = self.centre(self.current_ix);
+        let best = self
+            .room(self.current_ix)
+            .doors
+            .all_doors()
+            .filter(|(_, st)| *st == DoorState::Open)
+            .filter_map(|(dir, _)| {
+                let (nx, ny) = self.centre(self.neighbor(self.current_ix, dir)?);
+                let (vx, vy) = (nx - cx, ny - cy);
+                let len = vx.hypot(vy);
+                Some((dir, (vx * dx + vy * dy) / len))
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        match best {
+            Some((dir, _)) => self.move_dir(dir),
+            None => false,
+        }
+    }
+    pub fn visits(&self, ix: PolarIx) -> usize {
+        self.trail.iter().filter(|t| **t == ix).count()
+    }
+    pub fn max_visits(&self) -> usize {
+        self.indices().map(|ix| self.visits(ix)).max().unwrap_or(0)
+    }
+    pub fn is_done(&self) -> bool {
+        self.current_ix == self.goal
+    }
+    /// start and end angles of a room, in radians
+    pub fn angles(&self, ix: PolarIx) -> (f64, f64) {
+        let step = TAU / self.ring_len(ix.ring) as f64;
+        (step * ix.cell as f64, step * (ix.cell + 1) as f64)
+    }
+    /// centre of a room, in units of ring height from the centre of the maze
+    pub fn centre(&self, ix: PolarIx) -> (f64, f64) {
+        if ix.ring == 0 {
+            return (0.0, 0.0);
+        }
+        let (start, end) = self.angles(ix);
+        let theta = (start + end) / 2.0;
+        let r = ix.ring as f64 + 0.5;
+        (r * theta.cos(), r * theta.sin())
+    }
+}
+
+/// a room as `ring,cell`
+fn save_ix(ix: PolarIx) -> String {
+    format!("{},{}", ix.ring, ix.cell)
+}
+
+fn parse_ix(s: &str) -> Option<PolarIx> {
+    let (ring, cell) = s.split_once(',')?;
+    Some(PolarIx::new(ring.parse().ok()?, cell.parse().ok()?))
+}
+
+impl Save for PolarMaze {
+    /// one line per ring with a word for each room: a letter per door, in the order
+    /// [`PolarDoors::all_doors`] gives them, `o` for open and `x` for closed
+    fn save(&self, out: &mut Writer) {
+        out.field("rings", self.n_rings());
+        for rooms in &self.rings {
+            out.list(
+                "ring",
+                rooms.iter().map(|room| {
+                    room.doors
+                        .all_doors()
+                        .map(|(_, st)| st.token())
+                        .collect::<String>()
+                }),
+            );
+        }
+        out.field("current", save_ix(self.current_ix));
+        out.field("goal", save_ix(self.goal));
+        out.list("trail", self.trail.iter().map(|ix| save_ix(*ix)));
+    }
+    fn load(input: &mut Reader) -> Result<Self> {
+        let mut maze = Self::new(input.parse("rings")?);
+        for ring in 0..maze.n_rings() {
+            let words = input.list("ring", |word| Some(word.to_string()))?;
+            if words.len() != maze.ring_len(ring) {
+                bail!("expected {} rooms in ring {ring}", maze.ring_len(ring));
+            }
+            for (cell, word) in words.iter().enumerate() {
+                let doors = &mut maze.rings[ring][cell].doors;
+                let dirs: Vec<PolarDirection> = doors.all_doors().map(|(dir, _)| dir).collect();
+                if word.chars().count() != dirs.len() {
+                    bail!("bad room {word:?} at {ring},{cell}");
+                }
+                for (dir, c) in dirs.into_iter().zip(word.chars()) {
+                    let st = DoorState::from_token(&c.to_string())
+                        .ok_or_else(|| eyre!("bad room {word:?} at {ring},{cell}"))?;
+                    doors.set(dir, st);
+                }
+            }
+        }
+        let sizes: Vec<usize> = maze.rings.iter().map(Vec::len).collect();
+        let in_maze = |ix: &PolarIx| sizes.get(ix.ring).is_some_and(|size| ix.cell < *size);
+        let mut ix = |key| -> Result<PolarIx> {
+            parse_ix(input.field(key)?)
+                .filter(in_maze)
+                .ok_or_else(|| eyre!("bad {key} room"))
+        };
+        maze.current_ix = ix("current")?;
+        maze.goal = ix("goal")?;
+        maze.trail = input.list("trail", |s| parse_ix(s).filter(in_maze))?;
+        Ok(maze)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ring_sizes() {
+        let m = PolarMaze::new(5);
+        let sizes: Vec<usize> = m.rings.iter().map(Vec::len).collect();
+        assert_eq!(vec![1, 6, 12, 24, 24], sizes);
+    }
+
+    #[test]
+    fn test_open_outward() {
+        let mut m = PolarMaze::new(3);
+        let ix = PolarIx::new(1, 2);
+        m.open(ix, PolarDirection::Outward(1));
+        assert_eq!(
+            Some(DoorState::Open),
+            m.room(ix).doors.get(PolarDirection::Outward(1)),
+            "original room"
+        );
+        let child = PolarIx::new(2, 5);
+        assert_eq!(
+            Some(DoorState::Open),
+            m.room(child).doors.inward,
+            "neighbor"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{
+    Direction,
+    game::lantern::LightShape,
+    goals::WinCondition,
+    maze::{DoorState, Item},
+    ui::TrailView,
+};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, eyre},
+};
+use multid::BoundedIx2;
+use std::{
+    fmt::{self, Display, Write},
+    fs,
+    path::{Path, PathBuf},
+    str::{FromStr, Lines},
+    time::Duration,
+};
+
+/// where a game is saved when the player stops part way through
+pub const SAVE_PATH: &str = "saves/game.sav";
+/// first line of every save file, so old or foreign files get turned away
+const HEADER: &str = "samazing save 1";
+
+/// something that can be written to a save file and read back
+pub trait Save: Sized {
+    fn save(&self, out: &mut Writer);
+    fn load(input: &mut Reader) -> Result<Self>;
+}
+
+/// builds up a save as `key value` lines
+#[derive(Debug, Default)]
+pub struct Writer {
+    text: String,
+}
+
+impl Writer {
+    pub fn field(&mut self, key: &str, value: impl Display) {
+        writeln!(self.text, "{key} {value}").unwrap();
+    }
+    /// `key` and a list of values on one line, separated by spaces
+    pub fn list<T: Display>(&mut self, key: &str, values: impl IntoIterator<Item = T>) {
+        self.text.push_str(key);
+        for value in values {
+            write!(self.text, " {value}").unwrap();
+        }
+        self.text.push('\n');
+    }
+    pub fn finish(self) -> String {
+        self.text
+    }
+}
+
+/// reads back what a [`Writer`] wrote, in the same order
+#[derive(Debug)]
+pub struct Reader<'a> {
+    lines: Lines<'a>,
+    /// number of the last line read, for error messages
+    n: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            lines: text.lines(),
+            n: 0,
+        }
+    }
+    pub fn line(&mut self) -> Result<&'a str> {
+        self.n += 1;
+        self.lines
+            .next()
+            .ok_or_else(|| eyre!("line {}: save ends too soon", self.n))
+    }
+    /// whatever follows `key` on the next line
+    pub fn field(&mut self, key: &str) -> Result<&'a str> {
+        let line = self.line()?;
+        match line.strip_prefix(key) {
+            Some("") => Ok(""),
+            Some(rest) if rest.starts_with(' ') => Ok(&rest[1..]),
+            _ => bail!("line {}: expected {key}, got {line:?}", self.n),
+        }
+    }
+    pub fn parse<T: FromStr>(&mut self, key: &str) -> Result<T> {
+        let value = self.field(key)?;
+        value
+            .parse()
+            .map_err(|_| eyre!("line {}: bad {key} {value:?}", self.n))
+    }
+    /// a list of values written by [`Writer::list`]
+    pub fn list<T>(&mut self, key: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>> {
+        let n = self.n + 1;
+        self.field(key)?
+            .split_whitespace()
+            .map(|value| parse(value).ok_or_else(|| eyre!("line {n}: bad {key} {value:?}")))
+            .collect()
+    }
+    /// a value that might be `-` for none
+    pub fn optional<T: FromStr>(&mut self, key: &str) -> Result<Option<T>> {
+        match self.field(key)? {
+            "-" => Ok(None),
+            value => value
+                .parse()
+                .map(Some)
+                .map_err(|_| eyre!("line {}: bad {key} {value:?}", self.n)),
+        }
+    }
+    /// a simple enum written with [`Token::token`]
+    pub fn token<T: Token>(&mut self, key: &str) -> Result<T> {
+        let value = self.field(key)?;
+        T::from_token(value).ok_or_else(|| eyre!("line {}: bad {key} {value:?}", self.n))
+    }
+    /// fail unless the save is for a maze this size
+    pub fn size(&mut self, n_rows: usize, n_cols: usize) -> Result<()> {
+        let size = self.list("size", |s| s.parse::<usize>().ok())?;
+        if size != [n_rows, n_cols] {
+            bail!("saved maze is {size:?}, this one's {n_rows}x{n_cols}");
+        }
+        Ok(())
+    }
+}
+
+/// `-` for none, or the value
+pub fn optional<T: Display>(value: Option<T>) -> String {
+    match value {
+        None => "-".into(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// a room as `row,col`
+pub fn ix<const N_ROWS: usize, const N_COLS: usize>(ix: BoundedIx2<N_ROWS, N_COLS>) -> String {
+    format!("{},{}", ix.y(), ix.x())
+}
+
+/// a room written by [`ix`], and whatever else followed it after another comma
+pub fn parse_ix<const N_ROWS: usize, const N_COLS: usize>(
+    s: &str,
+) -> Option<(BoundedIx2<N_ROWS, N_COLS>, &str)> {
+    let mut parts = s.splitn(3, ',');
+    let row = parts.next()?.parse().ok()?;
+    let col = parts.next()?.parse().ok()?;
+    Some((BoundedIx2::new(row, col)?, parts.next().unwrap_or("")))
+}
+
+/// a short word for each value of a simple enum
+pub trait Token: Sized {
+    fn token(&self) -> &'static str;
+    fn from_token(s: &str) -> Option<Self>;
+}
+
+macro_rules! tokens {
+    ($ty:ty { $($value:path => $token:literal),* $(,)? }) => {
+        impl Token for $ty {
+            fn token(&self) -> &'static str {
+                match self {
+                    $($value => $token,)*
+                }
+            }
+            fn from_token(s: &str) -> Option<Self> {
+                match s {
+                    $($token => Some($value),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+tokens!(DoorState { DoorState::Open => "o", DoorState::Closed => "x" });
+tokens!(Direction {
+    Direction::North => "n",
+    Direction::East => "e",
+    Direction::South => "s",
+    Direction::West => "w",
+});
+tokens!(Item { Item::Coin => "c", Item::Gem => "g", Item::Fuel => "f" });
+tokens!(WinCondition {
+    WinCondition::Any => "any",
+    WinCondition::All => "all",
+    WinCondition::Ordered => "ordered",
+});
+tokens!(LightShape {
+    LightShape::Square => "square",
+    LightShape::Manhattan => "diamond",
+    LightShape::Doors => "doors",
+    LightShape::LineOfSight => "sight",
+});
+tokens!(TrailView {
+    TrailView::Off => "off",
+    TrailView::Breadcrumbs => "breadcrumbs",
+    TrailView::Heatmap => "heatmap",
+});
+
+/// a game saved part way through: enough to carry on where the player left off, or to start it
+/// again from the beginning
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedGame {
+    /// name of the game mode, as shown in the menu
+    pub mode: String,
+    pub seed: u64,
+    /// time spent playing so far
+    pub elapsed: Duration,
+    pub moves: usize,
+    /// the game as it was before the first move
+    pub start: String,
+    /// the game as it was when it was saved
+    pub current: String,
+}
+
+impl SavedGame {
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("couldn't create {}", dir.display()))?;
+        }
+        fs::write(path, self.to_string())
+            .wrap_err_with(|| format!("couldn't write {}", path.display()))?;
+        Ok(path.into())
+    }
+    /// the game saved at `path`, or `None` if nothing's been saved
+    pub fn read(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(path)
+            .wrap_err_with(|| format!("couldn't read {}", path.display()))?;
+        text.parse()
+            .map(Some)
+            .wrap_err_with(|| format!("couldn't load {}", path.display()))
+    }
+    /// what the menu says about the saved game
+    pub fn summary(&self) -> String {
+        format!(
+            "{}, {} moves, {}",
+            self.mode,
+            self.moves,
+            clock(self.elapsed)
+        )
+    }
+}
+
+impl Display for SavedGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(f, "mode {}", self.mode)?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "elapsed {}", self.elapsed.as_secs())?;
+        writeln!(f, "moves {}", self.moves)?;
+        for (key, block) in [("start", &self.start), ("current", &self.current)] {
+            writeln!(f, "{key} {}", block.lines().count())?;
+            for line in block.lines() {
+                writeln!(f, "{line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SavedGame {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut input = Reader::new(s);
+        if input.line()? != HEADER {
+            bail!("not a save file");
+        }
+        let mode = input.field("mode")?.to_string();
+        let seed = input.parse("seed")?;
+        let elapsed = Duration::from_secs(input.parse("elapsed")?);
+        let moves = input.parse("moves")?;
+        let mut block = |key| -> Result<String> {
+            let n: usize = input.parse(key)?;
+            let mut text = String::new();
+            for _ in 0..n {
+                text.push_str(input.line()?);
+                text.push('\n');
+            }
+            Ok(text)
+        };
+        let start = block("start")?;
+        let current = block("current")?;
+        Ok(Self {
+            mode,
+            seed,
+            elapsed,
+            moves,
+            start,
+            current,
+        })
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` for long games
+pub fn clock(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_saved_game() {
+        let saved = SavedGame {
+            mode: "Hidden".into(),
+            seed: 42,
+            elapsed: Duration::from_secs(192),
+            moves: 40,
+            start: "a 1\nb\n".into(),
+            current: "a 2\nb\nc 3 4\n".into(),
+        };
+        assert_eq!(saved, saved.to_string().parse().unwrap());
+        assert_eq!("Hidden, 40 moves, 3:12", saved.summary());
+        assert!("samazing save 0\n".parse::<SavedGame>().is_err());
+    }
+}
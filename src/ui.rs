@@ -1,11 +1,20 @@
 use crate::{
-    Direction,
-    maze::{DoorState, Room},
+    Direction, DirectionsIter,
+    chalk::{Chalk, Marks},
+    goals::{Goals, WinCondition},
+    maze::{Axis, DoorState, Item, Maze, Room, RoomKind},
+    polar::PolarDoors,
 };
+use multid::BoundedIx2;
 use ratatui::{
     Frame,
-    style::Color,
-    widgets::canvas::{Canvas, Context, Line, Painter, Shape},
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    widgets::{
+        Paragraph, Widget,
+        canvas::{Canvas, Context, Line, Painter, Rectangle, Shape},
+    },
 };
 
 pub const MIN_X: f64 = -200.0;
@@ -19,6 +28,39 @@ pub const BG_COLOR: Color = Color::Black;
 pub const WALL_COLOR: Color = Color::Green;
 pub const HIDDEN_WALL_COLOR: Color = Color::Gray;
 pub const DOOR_COLOR: Color = Color::Red;
+pub const WRAP_DOOR_COLOR: Color = Color::Cyan;
+pub const BRIDGE_COLOR: Color = Color::Yellow;
+pub const BREADCRUMB_COLOR: Color = Color::Magenta;
+pub const CHALK_COLOR: Color = Color::White;
+
+/// `color` as red, green and blue, using the xterm colours the terminal usually shows
+pub fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (192, 192, 192),
+        Color::White => (229, 229, 229),
+        Color::Rgb(r, g, b) => (r, g, b),
+        _ => (127, 127, 127),
+    }
+}
+
+/// `color` part of the way to the grey of walls nobody remembers, from 0.0 (not at all) to 1.0.
+/// the background doesn't fade, so open doors stay open however long ago the room was seen
+pub fn faded(color: Color, fade: f64) -> Color {
+    if fade <= 0.0 || color == BG_COLOR {
+        return color;
+    }
+    let (from, to) = (rgb(color), rgb(HIDDEN_WALL_COLOR));
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fade.min(1.0)).round() as u8;
+    Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
 pub fn render_maze<const N_ROWS: usize, const N_COLS: usize, F>(
     f: F,
 ) -> impl for<'a> FnOnce(&'a mut Frame)
@@ -33,37 +75,259 @@ where
     |frame: &mut Frame| frame.render_widget(widget, frame.area())
 }
 
+/// split `area` into the maze and a line underneath it for the HUD
+pub fn hud_areas(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area)
+}
+
+/// status line shown under the maze
+#[derive(Debug)]
+pub struct Hud {
+    pub items: Vec<String>,
+}
+
+impl Widget for Hud {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.items.join("  |  "))
+            .centered()
+            .fg(WALL_COLOR)
+            .bg(BG_COLOR)
+            .render(area, buf);
+    }
+}
+
+/// what to print in a goal's room: a tick once it's been reached, its number if the goals are
+/// checkpoints to be reached in order, or otherwise a goalpost
+pub fn goal_label<const N_ROWS: usize, const N_COLS: usize>(
+    goals: &Goals<N_ROWS, N_COLS>,
+    ix: BoundedIx2<N_ROWS, N_COLS>,
+) -> Option<String> {
+    let n = goals.position(ix)?;
+    if goals.is_reached(ix) {
+        Some("\u{2705}".into())
+    } else if goals.condition == WinCondition::Ordered && n + 1 < goals.len() {
+        Some(format!("{}", n + 1))
+    } else {
+        Some("\u{1f945}".into())
+    }
+}
+
+pub fn item_label(item: Item) -> &'static str {
+    match item {
+        Item::Coin => "\u{1fa99}",
+        Item::Gem => "\u{1f48e}",
+        Item::Fuel => "\u{26fd}",
+    }
+}
+
+/// how to show where the player has been
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum TrailView {
+    #[default]
+    Off,
+    /// a dot in every room the player has been in
+    Breadcrumbs,
+    /// dots coloured from blue to red by how often the player has been in each room
+    Heatmap,
+}
+
+/// a dot near the bottom left of a room at `(x, y)`, if the player has been there
+#[derive(Debug)]
+pub struct Breadcrumb {
+    pub x: f64,
+    pub y: f64,
+    pub visits: usize,
+    /// visits to the room the player has been in most, which gets the hottest colour
+    pub max_visits: usize,
+    pub view: TrailView,
+}
+
+impl Shape for Breadcrumb {
+    fn draw(&self, painter: &mut Painter<'_, '_>) {
+        if self.visits == 0 {
+            return;
+        }
+        let color = match self.view {
+            TrailView::Off => return,
+            TrailView::Breadcrumbs => BREADCRUMB_COLOR,
+            TrailView::Heatmap => {
+                let heat = match self.max_visits {
+                    0 | 1 => 0.0,
+                    max => (self.visits - 1) as f64 / (max - 1) as f64,
+                };
+                Color::Rgb((205.0 * heat) as u8, 0, (205.0 * (1.0 - heat)) as u8)
+            }
+        };
+        Rectangle {
+            x: self.x + SEG_LEN,
+            y: self.y - SEG_LEN * 6.0,
+            width: SEG_LEN,
+            height: SEG_LEN,
+            color,
+        }
+        .draw(painter);
+    }
+}
+
+/// the whole grid, with the rooms the player remembers drawn in and the rest left as outlines;
+/// it scales to whatever area it's given, so it works as a minimap too
+pub struct ExploredMap<'a, const N_ROWS: usize, const N_COLS: usize, F> {
+    pub maze: &'a Maze<N_ROWS, N_COLS>,
+    /// how faded each room the player remembers is, or `None` for rooms they don't
+    pub fade: F,
+    /// show goals the player hasn't found yet
+    pub all_goals: bool,
+    pub trail: TrailView,
+    pub chalk: &'a Chalk<N_ROWS, N_COLS>,
+}
+
+impl<'a, const N_ROWS: usize, const N_COLS: usize, F> Widget for ExploredMap<'a, N_ROWS, N_COLS, F>
+where
+    F: Fn(BoundedIx2<N_ROWS, N_COLS>) -> Option<f64>,
+{
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let maze = self.maze;
+        let is_seen = |ix: BoundedIx2<N_ROWS, N_COLS>| (self.fade)(ix).is_some();
+        let max_visits = maze.max_visits();
+        let c = Canvas::default()
+            .x_bounds([MIN_X, MAX_X])
+            .y_bounds([MIN_Y, MAX_Y])
+            .background_color(BG_COLOR)
+            .paint(|ctx| {
+                for ix in maze.indices() {
+                    let x = -200.0 + ROOM_SIZE * ix.x() as f64;
+                    let y = 200.0 - ROOM_SIZE * ix.y() as f64;
+                    let label_x = x + SEG_LEN * 3.5;
+                    let label_y = y - SEG_LEN * 3.5;
+                    if (self.all_goals || is_seen(ix))
+                        && let Some(label) = goal_label(&maze.goals, ix)
+                    {
+                        ctx.print(label_x, label_y, label)
+                    }
+                    if let Some(fade) = (self.fade)(ix) {
+                        let view = RoomView {
+                            x,
+                            y,
+                            room: &maze.rooms[ix],
+                            wrap_doors: maze.wrap_doors(ix),
+                            fade,
+                            marks: self.chalk.get(ix),
+                        };
+                        ctx.draw(&view);
+                        ctx.draw(&Breadcrumb {
+                            x,
+                            y,
+                            visits: maze.visits(ix),
+                            max_visits,
+                            view: self.trail,
+                        });
+                        if ix == maze.current_ix && maze.goals.contains(ix) {
+                            ctx.print(label_x, label_y, "\u{1f940}")
+                        } else if ix == maze.current_ix {
+                            ctx.print(label_x, label_y, "\u{1f600}")
+                        }
+                    } else {
+                        let hidden_walls = DirectionsIter::new()
+                            .filter(|dir| !maze.neighbor(ix, *dir).is_some_and(is_seen))
+                            .collect();
+                        ctx.draw(&UnseenRoomView { x, y, hidden_walls });
+                    }
+                }
+            });
+        c.render(area, buf);
+    }
+}
+
 #[derive(Debug)]
 pub struct RoomView<'a> {
     pub x: f64,
     pub y: f64,
     pub room: &'a Room,
+    /// doors that lead around to the other side of a wrapping maze
+    pub wrap_doors: Vec<Direction>,
+    /// how far the room has faded from memory, from 0.0 (just seen) to 1.0 (all but forgotten)
+    pub fade: f64,
+    pub marks: Option<&'a Marks>,
 }
 
-impl<'a> Shape for RoomView<'a> {
-    fn draw(&self, painter: &mut Painter<'_, '_>) {
-        let lines: &[Line] = &[
+impl<'a> RoomView<'a> {
+    fn door_color(&self, dir: Direction) -> Color {
+        let ds = match dir {
+            Direction::North => &self.room.doors.north,
+            Direction::East => &self.room.doors.east,
+            Direction::South => &self.room.doors.south,
+            Direction::West => &self.room.doors.west,
+        };
+        let color = if self.wrap_doors.contains(&dir) && *ds == Some(DoorState::Open) {
+            WRAP_DOOR_COLOR
+        } else {
+            door_state_color(ds)
+        };
+        faded(color, self.fade)
+    }
+    /// a cross in the top right corner for a marked room, and a tick just inside each door for
+    /// every time it was marked
+    fn mark_lines(&self, marks: &Marks) -> Vec<Line> {
+        let color = faded(CHALK_COLOR, self.fade);
+        let mut lines = Vec::new();
+        if marks.room {
+            for (y1, y2) in [(1.0, 2.0), (2.0, 1.0)] {
+                lines.push(Line {
+                    x1: self.x + SEG_LEN * 5.0,
+                    y1: self.y - SEG_LEN * y1,
+                    x2: self.x + SEG_LEN * 6.0,
+                    y2: self.y - SEG_LEN * y2,
+                    color,
+                });
+            }
+        }
+        for dir in DirectionsIter::new() {
+            for n in 0..marks.on_door(dir) {
+                let along = n as f64;
+                let (x1, y1, x2, y2) = match dir {
+                    Direction::North => (3.0 + along, 0.5, 3.0 + along, 1.5),
+                    Direction::South => (3.0 + along, 5.5, 3.0 + along, 6.5),
+                    Direction::West => (0.5, 3.5 + along, 1.5, 3.5 + along),
+                    Direction::East => (5.5, 3.5 + along, 6.5, 3.5 + along),
+                };
+                lines.push(Line {
+                    x1: self.x + SEG_LEN * x1,
+                    y1: self.y - SEG_LEN * y1,
+                    x2: self.x + SEG_LEN * x2,
+                    y2: self.y - SEG_LEN * y2,
+                    color,
+                });
+            }
+        }
+        lines
+    }
+    /// every line that makes up the room: walls, doors, the sides of a bridge and chalk marks.
+    /// anything else that draws rooms (like the svg export) uses these so it looks the same
+    pub fn lines(&self) -> Vec<Line> {
+        let wall_color = faded(WALL_COLOR, self.fade);
+        let bridge_color = faded(BRIDGE_COLOR, self.fade);
+        let mut lines = vec![
             // north
             Line {
                 x1: self.x,
                 y1: self.y,
                 x2: self.x + SEG_LEN * 2.0,
                 y2: self.y,
-                color: WALL_COLOR,
+                color: wall_color,
             },
             Line {
                 x1: self.x + SEG_LEN * 2.0,
                 y1: self.y,
                 x2: self.x + SEG_LEN * 5.0,
                 y2: self.y,
-                color: door_state_color(&self.room.doors.north),
+                color: self.door_color(Direction::North),
             },
             Line {
                 x1: self.x + SEG_LEN * 5.0,
                 y1: self.y,
                 x2: self.x + SEG_LEN * 7.0,
                 y2: self.y,
-                color: WALL_COLOR,
+                color: wall_color,
             },
             // west
             Line {
@@ -71,21 +335,21 @@ impl<'a> Shape for RoomView<'a> {
                 y1: self.y,
                 x2: self.x,
                 y2: self.y - SEG_LEN * 3.0,
-                color: WALL_COLOR,
+                color: wall_color,
             },
             Line {
                 x1: self.x,
                 y1: self.y - SEG_LEN * 3.0,
                 x2: self.x,
                 y2: self.y - SEG_LEN * 5.0,
-                color: door_state_color(&self.room.doors.west),
+                color: self.door_color(Direction::West),
             },
             Line {
                 x1: self.x,
                 y1: self.y - SEG_LEN * 5.0,
                 x2: self.x,
                 y2: self.y - SEG_LEN * 7.0,
-                color: WALL_COLOR,
+                color: wall_color,
             },
             // south
             Line {
@@ -93,21 +357,21 @@ impl<'a> Shape for RoomView<'a> {
                 y1: self.y - SEG_LEN * 7.0,
                 x2: self.x + SEG_LEN * 2.0,
                 y2: self.y - SEG_LEN * 7.0,
-                color: WALL_COLOR,
+                color: wall_color,
             },
             Line {
                 x1: self.x + SEG_LEN * 2.0,
                 y1: self.y - SEG_LEN * 7.0,
                 x2: self.x + SEG_LEN * 5.0,
                 y2: self.y - SEG_LEN * 7.0,
-                color: door_state_color(&self.room.doors.south),
+                color: self.door_color(Direction::South),
             },
             Line {
                 x1: self.x + SEG_LEN * 5.0,
                 y1: self.y - SEG_LEN * 7.0,
                 x2: self.x + SEG_LEN * 7.0,
                 y2: self.y - SEG_LEN * 7.0,
-                color: WALL_COLOR,
+                color: wall_color,
             },
             // east
             Line {
@@ -115,24 +379,75 @@ impl<'a> Shape for RoomView<'a> {
                 y1: self.y,
                 x2: self.x + SEG_LEN * 7.0,
                 y2: self.y - SEG_LEN * 3.0,
-                color: WALL_COLOR,
+                color: wall_color,
             },
             Line {
                 x1: self.x + SEG_LEN * 7.0,
                 y1: self.y - SEG_LEN * 3.0,
                 x2: self.x + SEG_LEN * 7.0,
                 y2: self.y - SEG_LEN * 5.0,
-                color: door_state_color(&self.room.doors.east),
+                color: self.door_color(Direction::East),
             },
             Line {
                 x1: self.x + SEG_LEN * 7.0,
                 y1: self.y - SEG_LEN * 5.0,
                 x2: self.x + SEG_LEN * 7.0,
                 y2: self.y - SEG_LEN * 7.0,
-                color: WALL_COLOR,
+                color: wall_color,
             },
         ];
-        for line in lines {
+        // the sides of the bridge run in line with the door frames of the corridor on top, so
+        // the corridor underneath looks like it's cut off by them
+        let rails: &[Line] = match self.room.kind {
+            RoomKind::Plain => &[],
+            RoomKind::Crossing {
+                over: Axis::NorthSouth,
+            } => &[
+                Line {
+                    x1: self.x + SEG_LEN * 2.0,
+                    y1: self.y,
+                    x2: self.x + SEG_LEN * 2.0,
+                    y2: self.y - SEG_LEN * 7.0,
+                    color: bridge_color,
+                },
+                Line {
+                    x1: self.x + SEG_LEN * 5.0,
+                    y1: self.y,
+                    x2: self.x + SEG_LEN * 5.0,
+                    y2: self.y - SEG_LEN * 7.0,
+                    color: bridge_color,
+                },
+            ],
+            RoomKind::Crossing {
+                over: Axis::EastWest,
+            } => &[
+                Line {
+                    x1: self.x,
+                    y1: self.y - SEG_LEN * 3.0,
+                    x2: self.x + SEG_LEN * 7.0,
+                    y2: self.y - SEG_LEN * 3.0,
+                    color: bridge_color,
+                },
+                Line {
+                    x1: self.x,
+                    y1: self.y - SEG_LEN * 5.0,
+                    x2: self.x + SEG_LEN * 7.0,
+                    y2: self.y - SEG_LEN * 5.0,
+                    color: bridge_color,
+                },
+            ],
+        };
+        lines.extend_from_slice(rails);
+        if let Some(marks) = self.marks {
+            lines.extend(self.mark_lines(marks));
+        }
+        lines
+    }
+}
+
+impl<'a> Shape for RoomView<'a> {
+    fn draw(&self, painter: &mut Painter<'_, '_>) {
+        for line in self.lines() {
             line.draw(painter)
         }
     }
@@ -208,6 +523,103 @@ impl Shape for UnseenRoomView {
         }
     }
 }
+/// part of a circle, from `start` to `end` radians counter-clockwise
+#[derive(Debug)]
+pub struct Arc {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    pub start: f64,
+    pub end: f64,
+    pub color: Color,
+}
+
+impl Shape for Arc {
+    fn draw(&self, painter: &mut Painter<'_, '_>) {
+        // one point per unit of arc length keeps long outer arcs solid
+        let steps = ((self.end - self.start) * self.radius)
+            .abs()
+            .ceil()
+            .max(1.0) as usize;
+        for step in 0..=steps {
+            let theta = self.start + (self.end - self.start) * step as f64 / steps as f64;
+            let arc_x = self.radius.mul_add(theta.cos(), self.x);
+            let arc_y = self.radius.mul_add(theta.sin(), self.y);
+            if let Some((x, y)) = painter.get_point(arc_x, arc_y) {
+                painter.paint(x, y, self.color);
+            }
+        }
+    }
+}
+
+/// a room in a circular maze, centred on the canvas origin
+///
+/// each room draws its inner arc and its counter-clockwise wall, so every wall is drawn
+/// exactly once; rooms on the rim also draw the outer arc
+#[derive(Debug)]
+pub struct PolarRoomView<'a> {
+    pub inner: f64,
+    pub outer: f64,
+    pub start: f64,
+    pub end: f64,
+    pub doors: &'a PolarDoors,
+}
+
+impl<'a> PolarRoomView<'a> {
+    fn draw_arc(&self, painter: &mut Painter<'_, '_>, radius: f64, door: &Option<DoorState>) {
+        let third = (self.end - self.start) / 3.0;
+        for (start, end, color) in [
+            (self.start, self.start + third, WALL_COLOR),
+            (self.start + third, self.end - third, door_state_color(door)),
+            (self.end - third, self.end, WALL_COLOR),
+        ] {
+            Arc {
+                x: 0.0,
+                y: 0.0,
+                radius,
+                start,
+                end,
+                color,
+            }
+            .draw(painter);
+        }
+    }
+    fn draw_radial(&self, painter: &mut Painter<'_, '_>, theta: f64, door: &Option<DoorState>) {
+        let third = (self.outer - self.inner) / 3.0;
+        for (r1, r2, color) in [
+            (self.inner, self.inner + third, WALL_COLOR),
+            (
+                self.inner + third,
+                self.outer - third,
+                door_state_color(door),
+            ),
+            (self.outer - third, self.outer, WALL_COLOR),
+        ] {
+            Line {
+                x1: r1 * theta.cos(),
+                y1: r1 * theta.sin(),
+                x2: r2 * theta.cos(),
+                y2: r2 * theta.sin(),
+                color,
+            }
+            .draw(painter);
+        }
+    }
+}
+
+impl<'a> Shape for PolarRoomView<'a> {
+    fn draw(&self, painter: &mut Painter<'_, '_>) {
+        if self.inner == 0.0 {
+            return;
+        }
+        self.draw_arc(painter, self.inner, &self.doors.inward);
+        self.draw_radial(painter, self.end, &self.doors.counter_clockwise);
+        if self.doors.outward.is_empty() {
+            self.draw_arc(painter, self.outer, &None);
+        }
+    }
+}
+
 fn door_state_color(ds: &Option<DoorState>) -> Color {
     match ds {
         None => WALL_COLOR,
@@ -215,3 +627,28 @@ fn door_state_color(ds: &Option<DoorState>) -> Color {
         Some(DoorState::Closed) => DOOR_COLOR,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_faded() {
+        assert_eq!(WALL_COLOR, faded(WALL_COLOR, 0.0));
+        assert_eq!(Color::Rgb(192, 192, 192), faded(WALL_COLOR, 1.0));
+        assert_eq!(BG_COLOR, faded(BG_COLOR, 0.5));
+        // chalk fades a little at a time rather than jumping to grey
+        assert_eq!(Color::Rgb(225, 225, 225), faded(CHALK_COLOR, 0.1));
+        let mut room = Room::default();
+        room.doors.east = Some(DoorState::Open);
+        let view = RoomView {
+            x: 0.0,
+            y: 0.0,
+            room: &room,
+            wrap_doors: Vec::new(),
+            fade: 0.5,
+            marks: None,
+        };
+        assert_eq!(BG_COLOR, view.door_color(Direction::East));
+    }
+}
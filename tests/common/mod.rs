@@ -0,0 +1,31 @@
+//! fixtures shared by the integration tests
+
+use rand::{SeedableRng, rngs::StdRng};
+use samazing::{
+    Direction,
+    game::{new_seeded, options::Options},
+    maze::Maze,
+};
+
+/// seed every test game is made from
+pub const SEED: u64 = 42;
+
+/// the maze the seeded game is played in with the default options
+pub fn maze() -> Maze<7, 7> {
+    new_seeded(&mut StdRng::seed_from_u64(SEED), &Options::default()).unwrap()
+}
+
+/// which way to go at each step of the way to win `maze`
+pub fn solution(maze: &Maze<7, 7>) -> Vec<Direction> {
+    maze.solution()
+        .unwrap()
+        .windows(2)
+        .map(|pair| {
+            let (dir, _) = maze
+                .moves_from(pair[0])
+                .find(|(_, next)| *next == pair[1])
+                .unwrap();
+            dir
+        })
+        .collect()
+}
@@ -0,0 +1,194 @@
+//! invariants every maze generator should keep, checked over lots of seeds and sizes
+
+use multid::iterators::V2Indices;
+use rand::{SeedableRng, rngs::StdRng};
+use samazing::{
+    Direction,
+    game::{
+        new_seeded, options::Options, placement::Placement, seed_doors_backtrack, seed_doors_naive,
+        seed_doors_path, seed_doors_weave, seed_polar_doors,
+    },
+    mask::Mask,
+    maze::{DoorState, Maze},
+    polar::PolarMaze,
+};
+
+const SEEDS: u64 = 100;
+
+type Seeder<const N_ROWS: usize, const N_COLS: usize> = fn(&mut Maze<N_ROWS, N_COLS>, &mut StdRng);
+
+/// every generator, and whether it should make a perfect maze
+fn seeders<const N_ROWS: usize, const N_COLS: usize>()
+-> [(&'static str, Seeder<N_ROWS, N_COLS>, bool); 4] {
+    [
+        ("naive", seed_doors_naive, false),
+        ("path", seed_doors_path, false),
+        ("backtrack", seed_doors_backtrack, true),
+        ("weave", seed_doors_weave, true),
+    ]
+}
+
+/// every door leads to a room, and that room has the same door back
+fn assert_symmetric<const N_ROWS: usize, const N_COLS: usize>(
+    maze: &Maze<N_ROWS, N_COLS>,
+    what: &str,
+) {
+    for ix in V2Indices::<N_ROWS, N_COLS>::new() {
+        let room = &maze.rooms[ix];
+        if !maze.is_room(ix) {
+            assert_eq!(
+                None,
+                room.all_doors().next(),
+                "{what}: door out of empty space at {ix}"
+            );
+            continue;
+        }
+        for (dir, st) in room.all_doors() {
+            assert_eq!(Some(st), room.doors.get(dir), "{what}: {ix} {dir:?}");
+            let Some(next) = maze.neighbor(ix, dir) else {
+                panic!("{what}: {ix} has a door {dir:?} off the grid");
+            };
+            assert_eq!(
+                Some(st),
+                maze.rooms[next].doors.get(dir.opposite()),
+                "{what}: {ix} {dir:?} doesn't match {next}"
+            );
+        }
+        if !maze.wrap {
+            let edges = [
+                (ix.y() == 0, Direction::North),
+                (ix.y() == N_ROWS - 1, Direction::South),
+                (ix.x() == 0, Direction::West),
+                (ix.x() == N_COLS - 1, Direction::East),
+            ];
+            for (on_edge, dir) in edges {
+                if on_edge {
+                    assert_eq!(
+                        None,
+                        room.doors.get(dir),
+                        "{what}: {ix} has a door {dir:?} off the grid"
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// how many pairs of rooms the player can move between directly, skipping crossings
+fn n_edges<const N_ROWS: usize, const N_COLS: usize>(maze: &Maze<N_ROWS, N_COLS>) -> usize {
+    let ends: usize = maze
+        .indices()
+        .filter(|ix| !maze.rooms[*ix].is_crossing())
+        .map(|ix| maze.moves_from(ix).count())
+        .sum();
+    ends / 2
+}
+
+fn check<const N_ROWS: usize, const N_COLS: usize>(mask: Mask<N_ROWS, N_COLS>) {
+    for wrap in [false, true] {
+        for (name, seeder, perfect) in seeders::<N_ROWS, N_COLS>() {
+            for seed in 0..SEEDS {
+                let what = format!("{name} {N_ROWS}x{N_COLS} wrap {wrap} seed {seed}");
+                let mut maze = Maze::<N_ROWS, N_COLS>::with_mask(mask.clone(), wrap);
+                seeder(&mut maze, &mut StdRng::seed_from_u64(seed));
+                assert_symmetric(&maze, &what);
+                let dists = maze.distances_from(maze.current_ix);
+                if perfect {
+                    let n_rooms = maze
+                        .indices()
+                        .filter(|ix| !maze.rooms[*ix].is_crossing())
+                        .count();
+                    assert_eq!(n_rooms - 1, n_edges(&maze), "{what}");
+                    assert!(
+                        maze.indices()
+                            .filter(|ix| !maze.rooms[*ix].is_crossing())
+                            .all(|ix| dists[ix].is_some()),
+                        "{what}: not every room can be reached"
+                    );
+                }
+                if name != "naive" {
+                    for goal in maze.goals.iter() {
+                        assert!(dists[goal].is_some(), "{what}: can't reach {goal}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sizes() {
+    check::<1, 1>(Mask::full());
+    check::<1, 6>(Mask::full());
+    check::<2, 2>(Mask::full());
+    check::<3, 5>(Mask::full());
+    check::<7, 7>(Mask::full());
+    check::<10, 4>(Mask::full());
+}
+
+#[test]
+fn test_shapes() {
+    for shape in ["donut", "heart"] {
+        let path = format!("{}/masks/{shape}.txt", env!("CARGO_MANIFEST_DIR"));
+        check::<7, 7>(Mask::from_file(path).unwrap());
+    }
+}
+
+#[test]
+fn test_polar() {
+    for n_rings in 1..7 {
+        for seed in 0..SEEDS {
+            let mut maze = PolarMaze::new(n_rings);
+            seed_polar_doors(&mut maze, &mut StdRng::seed_from_u64(seed));
+            let n_rooms = maze.indices().count();
+            let mut ends = 0;
+            for ix in maze.indices() {
+                for (dir, st) in maze.room(ix).doors.all_doors() {
+                    if st == DoorState::Open {
+                        ends += 1;
+                    }
+                    let next = maze.neighbor(ix, dir);
+                    assert!(
+                        next.is_some(),
+                        "{n_rings} rings seed {seed}: {ix:?} {dir:?} leads nowhere"
+                    );
+                    assert_eq!(
+                        Some(st),
+                        maze.room(next.unwrap()).doors.get(maze.reverse(ix, dir)),
+                        "{n_rings} rings seed {seed}: {ix:?} {dir:?} has no door back"
+                    );
+                }
+            }
+            assert_eq!(n_rooms - 1, ends / 2, "{n_rings} rings seed {seed}");
+        }
+    }
+}
+
+/// what the game does with a generator, placing the start, goals and items too
+#[test]
+fn test_new_seeded() {
+    for weave in [false, true] {
+        for placement in Placement::ALL {
+            let options = Options {
+                weave,
+                placement,
+                checkpoints: 2,
+                items: 15,
+                items_required: 10,
+                ..Options::default()
+            };
+            for seed in 0..SEEDS {
+                let what = format!("weave {weave} {placement:?} seed {seed}");
+                let maze: Maze<7, 7> =
+                    new_seeded(&mut StdRng::seed_from_u64(seed), &options).unwrap();
+                let dists = maze.distances_from(maze.current_ix);
+                for goal in maze.goals.iter() {
+                    assert!(dists[goal].is_some(), "{what}: can't reach goal {goal}");
+                }
+                for ix in maze.indices().filter(|ix| maze.rooms[*ix].item.is_some()) {
+                    assert!(dists[ix].is_some(), "{what}: can't reach the item in {ix}");
+                }
+            }
+        }
+    }
+}
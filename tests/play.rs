@@ -0,0 +1,131 @@
+mod common;
+
+use crossterm::event::KeyCode;
+use rand::{SeedableRng, rngs::StdRng};
+use ratatui::{Terminal, backend::TestBackend};
+use samazing::{
+    Direction,
+    events::{Scripted, key},
+    game::{Outcome, menu_loop, mode, modes, options::Options},
+    maze::Maze,
+    save::SavedGame,
+};
+use std::{env, fs, path::PathBuf, process};
+
+fn terminal() -> Terminal<TestBackend> {
+    Terminal::new(TestBackend::new(120, 60)).unwrap()
+}
+
+/// somewhere for a test to save games that isn't the real save file
+fn save_path(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("samazing-{name}-{}.sav", process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// keys that walk the shortest way from the start to the goal
+fn keys(maze: &Maze<7, 7>) -> String {
+    common::solution(maze)
+        .into_iter()
+        .map(|dir| match dir {
+            Direction::North => 'k',
+            Direction::South => 'j',
+            Direction::East => 'l',
+            Direction::West => 'h',
+        })
+        .collect()
+}
+
+/// play the seeded basic game with `keys`
+fn play(keys: &str) -> (Outcome, (usize, usize)) {
+    let basic = modes::<7, 7>()[0];
+    let mut game = (basic.new_game)(
+        &mut StdRng::seed_from_u64(common::SEED),
+        &Options::default(),
+    )
+    .unwrap();
+    let outcome = mode::run(&mut terminal(), game.as_mut(), &mut Scripted::keys(keys)).unwrap();
+    (outcome, game.position())
+}
+
+#[test]
+fn test_solve() {
+    let maze = common::maze();
+    let goal = maze.goals.last();
+    assert_eq!((Outcome::Win, (goal.y(), goal.x())), play(&keys(&maze)));
+}
+
+#[test]
+fn test_quit() {
+    let start = common::maze().current_ix;
+    assert_eq!((Outcome::Quit, (start.y(), start.x())), play("q"));
+}
+
+#[test]
+fn test_out_of_keys() {
+    let maze = common::maze();
+    let keys = keys(&maze);
+    let first = maze.solution().unwrap()[1];
+    // one step in and then nothing more to press
+    assert_eq!((Outcome::Quit, (first.y(), first.x())), play(&keys[..1]));
+}
+
+#[test]
+fn test_undo() {
+    let maze = common::maze();
+    let keys = keys(&maze);
+    let start = maze.current_ix;
+    let (outcome, position) = play(&format!("{}u", &keys[..1]));
+    assert_eq!(Outcome::Quit, outcome);
+    assert_eq!((start.y(), start.x()), position);
+}
+
+#[test]
+fn test_restart() {
+    let keys = keys(&common::maze());
+    assert_eq!(Outcome::Restart, play(&format!("{}r", &keys[..1])).0);
+    assert_eq!(Outcome::NewMaze, play("n").0);
+}
+
+/// everything on the screen, as one string
+fn screen(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+    buffer.content().iter().map(|cell| cell.symbol()).collect()
+}
+
+#[test]
+fn test_menu() {
+    let save_path = save_path("menu");
+    // quit from the menu
+    let mut term = terminal();
+    let mut events = Scripted::new([key(KeyCode::Char('q')), key(KeyCode::Enter)]);
+    menu_loop::<7, 7, _>(&mut term, &mut events, &save_path).unwrap();
+    assert!(screen(&term).contains("Basic"));
+    assert!(!screen(&term).contains("you quit"));
+    // play a game, give up on it, then run out of keys back at the menu
+    let mut term = terminal();
+    let mut events = Scripted::new([key(KeyCode::Enter), key(KeyCode::Char('q'))]);
+    menu_loop::<7, 7, _>(&mut term, &mut events, &save_path).unwrap();
+    assert!(screen(&term).contains("you quit"));
+    // nothing to press at all
+    let mut term = terminal();
+    menu_loop::<7, 7, _>(&mut term, &mut Scripted::keys(""), &save_path).unwrap();
+    assert!(!screen(&term).contains("you quit"));
+    // giving up isn't saving
+    assert!(!save_path.exists());
+}
+
+#[test]
+fn test_save_and_continue() {
+    let save_path = save_path("continue");
+    // start the first game and save it straight away
+    let mut events = Scripted::new([key(KeyCode::Enter), key(KeyCode::Char('x'))]);
+    menu_loop::<7, 7, _>(&mut terminal(), &mut events, &save_path).unwrap();
+    let saved = SavedGame::read(&save_path).unwrap().unwrap();
+    assert_eq!("Basic", saved.mode);
+    assert_eq!(0, saved.moves);
+    // continue is at the top of the menu now; carrying on uses up the save
+    let mut events = Scripted::new([key(KeyCode::Enter), key(KeyCode::Char('q'))]);
+    menu_loop::<7, 7, _>(&mut terminal(), &mut events, &save_path).unwrap();
+    assert_eq!(None, SavedGame::read(&save_path).unwrap());
+}
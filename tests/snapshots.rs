@@ -0,0 +1,226 @@
+//! renders widgets into a `TestBackend` and compares the text against `tests/snapshots/*.txt`.
+//! run with `UPDATE_SNAPSHOTS=1` to write new snapshots when the output changes on purpose
+
+mod common;
+
+use rand::{SeedableRng, rngs::StdRng};
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    text::Span,
+    widgets::{
+        Widget,
+        canvas::{Canvas, Shape},
+    },
+};
+use samazing::{
+    Direction,
+    game::{
+        menu::{GameMenu, MenuState},
+        modes,
+        options::Options,
+    },
+    maze::{DoorState, Maze, Room},
+    movement::MazeEvent,
+    ui::{self, RoomView, UnseenRoomView},
+};
+use std::{env, fs, path::PathBuf};
+
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 40;
+
+/// the buffer as plain text, one line per row without trailing spaces, followed by the
+/// foreground colour of every cell so doors and fading show up too
+fn text(buffer: &Buffer) -> String {
+    let mut symbols = String::new();
+    let mut colors = String::new();
+    for y in 0..buffer.area.height {
+        let mut line = String::new();
+        let mut color_line = String::new();
+        let mut skip = 0;
+        for x in 0..buffer.area.width {
+            let cell = &buffer[(x, y)];
+            color_line.push(color_char(cell.fg));
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            skip = Span::raw(cell.symbol()).width().saturating_sub(1);
+            line.push_str(cell.symbol());
+        }
+        symbols.push_str(line.trim_end());
+        symbols.push('\n');
+        colors.push_str(color_line.trim_end_matches('.'));
+        colors.push('\n');
+    }
+    format!("{symbols}\n{colors}")
+}
+
+fn color_char(color: Color) -> char {
+    match color {
+        Color::Reset | Color::Black => '.',
+        Color::Green => 'g',
+        Color::Gray => 'a',
+        Color::Red => 'r',
+        Color::Cyan => 'c',
+        Color::Yellow => 'y',
+        Color::Magenta => 'm',
+        Color::White => 'w',
+        Color::Blue => 'b',
+        _ => '#',
+    }
+}
+
+fn assert_snapshot(name: &str, buffer: &Buffer) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+    let actual = text(buffer);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no snapshot at {}, run with UPDATE_SNAPSHOTS=1 to make one",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "{name} doesn't match its snapshot, run with UPDATE_SNAPSHOTS=1 if that's on purpose\n\
+         expected:\n{expected}\nactual:\n{actual}"
+    );
+}
+
+/// draw `shape` on a canvas just big enough for one room
+fn render_room(shape: &impl Shape) -> Buffer {
+    let area = Rect::new(0, 0, 20, 10);
+    let mut buffer = Buffer::empty(area);
+    let margin = ui::SEG_LEN;
+    Canvas::default()
+        .x_bounds([-margin, ui::ROOM_SIZE + margin])
+        .y_bounds([-ui::ROOM_SIZE - margin, margin])
+        .paint(|ctx| ctx.draw(shape))
+        .render(area, &mut buffer);
+    buffer
+}
+
+/// render the `n`th game on the menu after playing `events`
+fn render_game(n: usize, events: impl IntoIterator<Item = MazeEvent>) -> Buffer {
+    render_game_with(n, &Options::default(), events)
+}
+
+fn render_game_with(
+    n: usize,
+    options: &Options,
+    events: impl IntoIterator<Item = MazeEvent>,
+) -> Buffer {
+    let mode = modes::<7, 7>()[n];
+    let mut game = (mode.new_game)(&mut StdRng::seed_from_u64(common::SEED), options).unwrap();
+    for event in events {
+        game.handle_event(event);
+        game.update();
+    }
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal.draw(|frame| game.render(frame)).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// the first move on the shortest way from the start to the goal
+fn first_step(maze: &Maze<7, 7>) -> MazeEvent {
+    steps(maze, 1).pop().unwrap()
+}
+
+/// the first `n` moves on the shortest way from the start to the goal
+fn steps(maze: &Maze<7, 7>, n: usize) -> Vec<MazeEvent> {
+    common::solution(maze)
+        .into_iter()
+        .take(n)
+        .map(|dir| match dir {
+            Direction::North => MazeEvent::MoveN,
+            Direction::South => MazeEvent::MoveS,
+            Direction::East => MazeEvent::MoveE,
+            Direction::West => MazeEvent::MoveW,
+        })
+        .collect()
+}
+
+#[test]
+fn test_room_view() {
+    let mut room = Room::default();
+    room.doors.north = Some(DoorState::Closed);
+    room.doors.east = Some(DoorState::Open);
+    room.doors.south = Some(DoorState::Open);
+    let view = RoomView {
+        x: 0.0,
+        y: 0.0,
+        room: &room,
+        wrap_doors: vec![Direction::South],
+        fade: 0.0,
+        marks: None,
+    };
+    assert_snapshot("room_view", &render_room(&view));
+}
+
+#[test]
+fn test_unseen_room_view() {
+    let view = UnseenRoomView {
+        x: 0.0,
+        y: 0.0,
+        hidden_walls: vec![Direction::North, Direction::West],
+    };
+    assert_snapshot("unseen_room_view", &render_room(&view));
+}
+
+#[test]
+fn test_basic_game() {
+    assert_snapshot("basic_game", &render_game(0, []));
+}
+
+#[test]
+fn test_hidden_game() {
+    assert_snapshot("hidden_game", &render_game(1, []));
+    assert_snapshot(
+        "hidden_game_moved",
+        &render_game(1, [first_step(&common::maze())]),
+    );
+    // rooms seen a few moves ago are fading, with their open doors still open
+    let options = Options {
+        memory: Some(10),
+        ..Options::default()
+    };
+    assert_snapshot(
+        "hidden_game_fading",
+        &render_game_with(1, &options, steps(&common::maze(), 5)),
+    );
+}
+
+#[test]
+fn test_lantern_game() {
+    assert_snapshot("lantern_game", &render_game(2, []));
+    assert_snapshot(
+        "lantern_game_minimap",
+        &render_game(
+            2,
+            [
+                first_step(&common::maze()),
+                MazeEvent::OtherKey(crossterm::event::KeyCode::Char('m')),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn test_game_menu() {
+    let mut state = MenuState::new(modes::<7, 7>().iter().map(|mode| mode.name).collect());
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal
+        .draw(|frame| frame.render_stateful_widget(GameMenu, frame.area(), &mut state))
+        .unwrap();
+    assert_snapshot("game_menu", terminal.backend().buffer());
+}